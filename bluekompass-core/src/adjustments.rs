@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+
+/// Fraction of the darkest and brightest pixels ignored by auto-levels
+const AUTO_LEVELS_CLIP: f64 = 0.005;
+
+/// Display adjustments of an image, applied to a copy of its pixels only
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Adjustments {
+    /// Offset added to every channel, in `-1..=1`
+    pub brightness: f32,
+    /// Factor applied around the middle gray
+    pub contrast: f32,
+    pub gamma: f32,
+    pub invert: bool,
+    pub grayscale: bool,
+    /// Stretch the intensities so that they span the whole range
+    pub auto_levels: bool,
+}
+
+impl Default for Adjustments {
+    fn default() -> Self {
+        Self {
+            brightness: 0.0,
+            contrast: 1.0,
+            gamma: 1.0,
+            invert: false,
+            grayscale: false,
+            auto_levels: false,
+        }
+    }
+}
+
+/// Rec. 709 luma of an sRGB pixel
+pub fn luma([r, g, b]: [u8; 3]) -> u8 {
+    (0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32).round() as u8
+}
+
+/// Darkest and brightest luma once the extreme pixels are ignored
+fn levels(rgba: &[u8]) -> (u8, u8) {
+    let mut histogram = [0usize; 256];
+    for pixel in rgba.chunks_exact(4) {
+        histogram[luma([pixel[0], pixel[1], pixel[2]]) as usize] += 1;
+    }
+    let clipped = ((rgba.len() / 4) as f64 * AUTO_LEVELS_CLIP) as usize;
+    let bound = |mut values: Box<dyn Iterator<Item = usize>>| {
+        let mut count = 0;
+        values.find(|&value| {
+            count += histogram[value];
+            count > clipped
+        })
+    };
+    let low = bound(Box::new(0..256)).unwrap_or(0);
+    let high = bound(Box::new((0..256).rev())).unwrap_or(255);
+    if low < high { (low as u8, high as u8) } else { (0, 255) }
+}
+
+impl Adjustments {
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Adjust RGBA pixels in place, alpha is kept
+    pub fn apply(&self, rgba: &mut [u8]) {
+        if self.is_identity() {
+            return;
+        }
+        let (low, high) = if self.auto_levels { levels(rgba) } else { (0, 255) };
+
+        // Every adjustment but grayscale works per channel
+        let lookup: Vec<u8> = (0..=255u8)
+            .map(|value| {
+                let mut value = (value as f32 - low as f32) / (high - low) as f32;
+                value = (value - 0.5) * self.contrast + 0.5 + self.brightness;
+                value = value.clamp(0., 1.).powf(1. / self.gamma.max(0.01));
+                if self.invert {
+                    value = 1. - value;
+                }
+                (value * 255.).round() as u8
+            })
+            .collect();
+
+        for pixel in rgba.chunks_exact_mut(4) {
+            if self.grayscale {
+                let gray = luma([pixel[0], pixel[1], pixel[2]]);
+                pixel[..3].fill(gray);
+            }
+            for channel in &mut pixel[..3] {
+                *channel = lookup[*channel as usize];
+            }
+        }
+    }
+}
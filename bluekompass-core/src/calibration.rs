@@ -0,0 +1,141 @@
+use serde::{Deserialize, Serialize};
+
+use crate::coordinates::{Frame, YAxis};
+use crate::point::Point;
+
+/// Conversion from image pixels to physical units and read out coordinates
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Calibration {
+    /// Length of one pixel in `unit`
+    pub scale: f64,
+    pub unit: String,
+    #[serde(default)]
+    pub y_axis: YAxis,
+    /// Datum frames of the part, coordinates are relative to the image when none is active
+    #[serde(default)]
+    pub frames: Vec<Frame>,
+    #[serde(default)]
+    pub active_frame: Option<usize>,
+}
+
+impl Default for Calibration {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            unit: "px".to_string(),
+            y_axis: YAxis::default(),
+            frames: Vec::new(),
+            active_frame: None,
+        }
+    }
+}
+
+impl Calibration {
+    /// Calibration where `pixels` image pixels measure `length` units, `None` for a null or invalid length
+    pub fn from_reference(pixels: f64, length: f64, unit: impl Into<String>) -> Option<Self> {
+        let scale = length / pixels;
+        (scale.is_finite() && scale > 0.).then(|| Self { scale, unit: unit.into(), ..Self::default() })
+    }
+
+    pub fn is_calibrated(&self) -> bool {
+        let default = Self::default();
+        self.scale != default.scale || self.unit != default.unit
+    }
+
+    pub fn length(&self, pixels: f64) -> f64 {
+        pixels * self.scale
+    }
+
+    pub fn area(&self, pixels: f64) -> f64 {
+        pixels * self.scale * self.scale
+    }
+
+    pub fn frame(&self) -> Option<&Frame> {
+        self.frames.get(self.active_frame?)
+    }
+
+    /// Coordinates in pixels of a plot point in the active frame, along the chosen Y axis
+    pub fn frame_point(&self, point: Point) -> Point {
+        let point = self.frame().map_or(point, |frame| frame.to_frame(point));
+        self.y_axis.apply(point)
+    }
+
+    /// Plot point of coordinates given in pixels in the active frame, the inverse of `frame_point`
+    pub fn unframe_point(&self, point: Point) -> Point {
+        let point = self.y_axis.apply(point);
+        self.frame().map_or(point, |frame| frame.from_frame(point))
+    }
+
+    /// Coordinates of a plot point in the active frame, along the chosen Y axis
+    pub fn point(&self, point: Point) -> Point {
+        let point = self.frame_point(point);
+        Point::new(self.length(point.x), self.length(point.y))
+    }
+
+    /// Plot point of coordinates given in calibrated units, the inverse of `point`
+    pub fn plot_point(&self, coordinates: Point) -> Point {
+        self.unframe_point(Point::new(coordinates.x / self.scale, coordinates.y / self.scale))
+    }
+
+    /// Angle in degrees of a plot direction from the X axis of the active frame, along the chosen Y axis
+    pub fn angle(&self, degrees: f64) -> f64 {
+        let rotation = self.frame().map_or(0., |frame| frame.rotation.to_degrees());
+        let angle = self.y_axis.angle(degrees - rotation);
+        // Back to -180..=180
+        180. - (180. - angle).rem_euclid(360.)
+    }
+}
+
+/// Unit of length of the resolution stored in image files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResolutionUnit {
+    Inch,
+    Centimeter,
+    Meter,
+}
+
+impl ResolutionUnit {
+    fn millimeters(self) -> f64 {
+        match self {
+            ResolutionUnit::Inch => 25.4,
+            ResolutionUnit::Centimeter => 10.,
+            ResolutionUnit::Meter => 1000.,
+        }
+    }
+}
+
+/// Largest relative difference between the sizes of a pixel along X and Y for the pixels to be square
+pub const SQUARE_PIXEL_TOLERANCE: f64 = 1e-3;
+
+/// Pixels per unit of length along X and Y, as image files give them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Resolution {
+    pub x: f64,
+    pub y: f64,
+    pub unit: ResolutionUnit,
+}
+
+impl Resolution {
+    /// Size of a pixel in millimeters along X and Y, `None` for a null or invalid resolution
+    pub fn pixel_size(&self) -> Option<[f64; 2]> {
+        let size = [self.x, self.y].map(|pixels| self.unit.millimeters() / pixels);
+        size.iter().all(|length| length.is_finite() && *length > 0.).then_some(size)
+    }
+
+    /// Dots per inch along X and Y
+    pub fn dpi(&self) -> Option<[f64; 2]> {
+        self.pixel_size().map(|size| size.map(|length| 25.4 / length))
+    }
+
+    /// Whether the pixels have the same size along X and Y, within `SQUARE_PIXEL_TOLERANCE`
+    pub fn is_square(&self) -> bool {
+        self.pixel_size().is_some_and(|[x, y]| (x - y).abs() <= SQUARE_PIXEL_TOLERANCE * x.max(y))
+    }
+
+    /// Calibration in millimeters, `None` for pixels that are not square since a single scale would be wrong along
+    /// one of the axes
+    pub fn calibration(&self) -> Option<Calibration> {
+        let [x, _] = self.pixel_size().filter(|_| self.is_square())?;
+        Calibration::from_reference(1., x, "mm")
+    }
+}
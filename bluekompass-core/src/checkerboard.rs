@@ -0,0 +1,280 @@
+use crate::homography::Homography;
+use crate::intensity::Intensities;
+use crate::maths::solve;
+use crate::point::Point;
+
+/// Standard deviation in pixels of the blur before looking for corners
+const BLUR: f64 = 1.5;
+
+/// Radius in pixels of the circle sampled around a candidate corner, the squares must be larger
+const RING_RADIUS: f64 = 5.;
+
+const RING_SAMPLES: usize = 32;
+
+/// Fraction of the strongest response a candidate corner must reach
+const RESPONSE_THRESHOLD: f64 = 0.05;
+
+/// Half size in pixels of the window the saddle point is fitted in
+const FIT_RADIUS: isize = 2;
+
+/// Luma of an image once blurred, with the row of the top of the image first
+struct Blurred {
+    width: usize,
+    height: usize,
+    values: Vec<f64>,
+}
+
+impl Blurred {
+    fn new(image: &Intensities) -> Self {
+        let [width, height] = image.size();
+        let radius = (3. * BLUR).ceil() as isize;
+        let mut kernel: Vec<(isize, f64)> = (-radius..=radius)
+            .map(|offset| (offset, (-0.5 * (offset as f64 / BLUR).powi(2)).exp()))
+            .collect();
+        let total: f64 = kernel.iter().map(|(_, weight)| weight).sum();
+        kernel.iter_mut().for_each(|(_, weight)| *weight /= total);
+        let clamp = |value: isize, length: usize| value.clamp(0, length as isize - 1) as usize;
+
+        // Along the rows then along the columns, whole rows at a time
+        let mut luma = vec![0.; width];
+        let mut rows = vec![0.; width * height];
+        for y in 0..height {
+            for (x, value) in luma.iter_mut().enumerate() {
+                *value = image.luma(x, y) as f64;
+            }
+            let row = &mut rows[y * width..(y + 1) * width];
+            for &(offset, weight) in &kernel {
+                for (x, value) in row.iter_mut().enumerate() {
+                    *value += weight * luma[clamp(x as isize + offset, width)];
+                }
+            }
+        }
+        let mut values = vec![0.; width * height];
+        for y in 0..height {
+            let row = &mut values[y * width..(y + 1) * width];
+            for &(offset, weight) in &kernel {
+                let source = clamp(y as isize + offset, height) * width;
+                for (value, blurred) in row.iter_mut().zip(&rows[source..source + width]) {
+                    *value += weight * blurred;
+                }
+            }
+        }
+        Self { width, height, values }
+    }
+
+    fn at(&self, x: isize, y: isize) -> f64 {
+        let x = x.clamp(0, self.width as isize - 1) as usize;
+        let y = y.clamp(0, self.height as isize - 1) as usize;
+        self.values[y * self.width + x]
+    }
+
+    /// Value at a position in pixels, interpolated between the centers of the four nearest pixels
+    fn sample(&self, pixel: Point) -> f64 {
+        let (u, v) = (pixel.x - 0.5, pixel.y - 0.5);
+        let (x0, y0) = (u.floor(), v.floor());
+        let (tx, ty) = (u - x0, v - y0);
+        let (x0, y0) = (x0 as isize, y0 as isize);
+        let top = self.at(x0, y0) * (1. - tx) + self.at(x0 + 1, y0) * tx;
+        let bottom = self.at(x0, y0 + 1) * (1. - tx) + self.at(x0 + 1, y0 + 1) * tx;
+        top * (1. - ty) + bottom * ty
+    }
+
+    /// Strength of a saddle point at the center of a pixel, from the determinant of the Hessian
+    fn saddle(&self, x: isize, y: isize) -> f64 {
+        let center = self.at(x, y);
+        let xx = self.at(x + 1, y) - 2. * center + self.at(x - 1, y);
+        let yy = self.at(x, y + 1) - 2. * center + self.at(x, y - 1);
+        let xy = 0.25 * (self.at(x + 1, y + 1) - self.at(x + 1, y - 1) - self.at(x - 1, y + 1) + self.at(x - 1, y - 1));
+        xy * xy - xx * yy
+    }
+
+    /// Whether the values around a point change from dark to bright four times, as around the corner of two squares
+    fn is_crossing(&self, point: Point) -> bool {
+        let ring: Vec<f64> = (0..RING_SAMPLES)
+            .map(|index| {
+                let angle = std::f64::consts::TAU * index as f64 / RING_SAMPLES as f64;
+                self.sample(Point::new(point.x + RING_RADIUS * angle.cos(), point.y + RING_RADIUS * angle.sin()))
+            })
+            .collect();
+        let mean = ring.iter().sum::<f64>() / RING_SAMPLES as f64;
+        let changes = (0..RING_SAMPLES)
+            .filter(|&index| (ring[index] > mean) != (ring[(index + 1) % RING_SAMPLES] > mean))
+            .count();
+        changes == 4
+    }
+
+    /// Saddle point of a quadratic fitted around a point, `None` when there is none nearby
+    fn refine(&self, start: Point) -> Option<Point> {
+        let mut point = start;
+        for _ in 0..5 {
+            // f = a x² + b x y + c y² + d x + e y + g
+            let mut normal = vec![vec![0.; 6]; 6];
+            let mut right = vec![0.; 6];
+            for dy in -FIT_RADIUS..=FIT_RADIUS {
+                for dx in -FIT_RADIUS..=FIT_RADIUS {
+                    let (x, y) = (dx as f64, dy as f64);
+                    let terms = [x * x, x * y, y * y, x, y, 1.];
+                    let value = self.sample(Point::new(point.x + x, point.y + y));
+                    for (row, term) in terms.iter().enumerate() {
+                        for (column, other) in terms.iter().enumerate() {
+                            normal[row][column] += term * other;
+                        }
+                        right[row] += term * value;
+                    }
+                }
+            }
+            let [a, b, c, d, e, _] = <[f64; 6]>::try_from(solve(normal, right)?).ok()?;
+            let determinant = 4. * a * c - b * b;
+            if determinant >= 0. {
+                return None;
+            }
+            let shift = Point::new((b * e - 2. * c * d) / determinant, (b * d - 2. * a * e) / determinant);
+            point = Point::new(point.x + shift.x, point.y + shift.y);
+            if (point.x - start.x).hypot(point.y - start.y) > 2. {
+                return None;
+            }
+            if shift.x.hypot(shift.y) < 0.01 {
+                break;
+            }
+        }
+        Some(point)
+    }
+}
+
+/// Points of the convex hull, counterclockwise with the Y axis up
+fn convex_hull(points: &[Point]) -> Vec<Point> {
+    let mut sorted = points.to_vec();
+    sorted.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    let cross = |o: Point, a: Point, b: Point| (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x);
+    let mut hull: Vec<Point> = Vec::new();
+    for pass in [sorted.clone(), sorted.into_iter().rev().collect()] {
+        let start = hull.len();
+        for point in pass {
+            while hull.len() >= start + 2 && cross(hull[hull.len() - 2], hull[hull.len() - 1], point) <= 0. {
+                hull.pop();
+            }
+            hull.push(point);
+        }
+        hull.pop();
+    }
+    hull
+}
+
+/// Corners of the largest quadrilateral of the hull, in the order of the hull
+///
+/// For each first corner and each opposite corner, the two others are the farthest points on either side of the
+/// diagonal. Along a convex hull they only move forward as the opposite corner does, so the search is quadratic.
+fn outer_corners(hull: &[Point]) -> Option<[Point; 4]> {
+    let n = hull.len();
+    if n < 4 {
+        return None;
+    }
+    let triangle = |a: Point, b: Point, c: Point| ((b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)).abs();
+    let mut best: Option<([Point; 4], f64)> = None;
+    for i in 0..n {
+        // Offsets from the first corner along the hull
+        let at = |offset: usize| hull[(i + offset) % n];
+        let (mut j, mut l) = (1, 3);
+        for k in 2..n - 1 {
+            while j + 1 < k && triangle(at(0), at(j + 1), at(k)) >= triangle(at(0), at(j), at(k)) {
+                j += 1;
+            }
+            l = l.max(k + 1);
+            while l + 1 < n && triangle(at(0), at(k), at(l + 1)) >= triangle(at(0), at(k), at(l)) {
+                l += 1;
+            }
+            let area = triangle(at(0), at(j), at(k)) + triangle(at(0), at(k), at(l));
+            if best.is_none_or(|(_, best_area)| area > best_area) {
+                best = Some(([at(0), at(j), at(k), at(l)], area));
+            }
+        }
+    }
+    best.map(|(quad, _)| quad)
+}
+
+/// Corners ordered row by row on the grid, from the outer corners of the board
+fn order(points: &[Point], [columns, rows]: [usize; 2]) -> Option<Vec<Point>> {
+    let quad = outer_corners(&convex_hull(points))?;
+    let [last_column, last_row] = [(columns - 1) as f64, (rows - 1) as f64];
+    let grid_corners = [(0., 0.), (last_column, 0.), (last_column, last_row), (0., last_row)].map(|(x, y)| Point::new(x, y));
+
+    // Any outer corner may be the first one, along either side
+    let mut best: Option<(Vec<Point>, f64)> = None;
+    for reversed in [false, true] {
+        for shift in 0..4 {
+            let quad = [0, 1, 2, 3].map(|index| if reversed { quad[(shift + 4 - index) % 4] } else { quad[(shift + index) % 4] });
+            let Some(homography) = Homography::from_points(quad, grid_corners) else {
+                continue;
+            };
+            let mut cells: Vec<Option<Point>> = vec![None; columns * rows];
+            let mut error = 0.;
+            let assigned = points.iter().all(|&point| {
+                let Some(position) = homography.apply(point) else {
+                    return false;
+                };
+                let (x, y) = (position.x.round(), position.y.round());
+                if !(0. ..=last_column).contains(&x) || !(0. ..=last_row).contains(&y) {
+                    return false;
+                }
+                error += (position.x - x).powi(2) + (position.y - y).powi(2);
+                let cell = &mut cells[y as usize * columns + x as usize];
+                cell.replace(point).is_none()
+            });
+            if assigned && best.as_ref().is_none_or(|(_, best_error)| error < *best_error) {
+                best = Some((cells.into_iter().collect::<Option<Vec<_>>>()?, error));
+            }
+        }
+    }
+    best.map(|(corners, _)| corners)
+}
+
+/// Inner corners of a checkerboard of `columns × rows` inner corners, in pixels, row by row from one of the
+/// corners of the board, `None` when they are not all found
+///
+/// The squares must be larger than about a dozen pixels, the strongest corners are kept when the image has more.
+pub fn find_corners(image: &Intensities, [columns, rows]: [usize; 2]) -> Option<Vec<Point>> {
+    let [width, height] = image.size();
+    if columns < 2 || rows < 2 || width < 3 || height < 3 {
+        return None;
+    }
+    let blurred = Blurred::new(image);
+    let response: Vec<f64> = (0..height * width)
+        .map(|index| blurred.saddle((index % width) as isize, (index / width) as isize))
+        .collect();
+    let strongest = response.iter().copied().fold(0., f64::max);
+    if strongest <= 0. {
+        return None;
+    }
+
+    // Local maxima of the response
+    let margin = RING_RADIUS.ceil() as usize + 1;
+    let mut candidates: Vec<(Point, f64)> = Vec::new();
+    for y in margin..height.saturating_sub(margin) {
+        for x in margin..width.saturating_sub(margin) {
+            let value = response[y * width + x];
+            let is_maximum = value > RESPONSE_THRESHOLD * strongest
+                && (y - 2..=y + 2).all(|ny| (x - 2..=x + 2).all(|nx| response[ny * width + nx] <= value));
+            if is_maximum {
+                candidates.push((Point::new(x as f64 + 0.5, y as f64 + 0.5), value));
+            }
+        }
+    }
+    candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    let mut corners: Vec<Point> = Vec::new();
+    for (candidate, _) in candidates {
+        if !blurred.is_crossing(candidate) {
+            continue;
+        }
+        let Some(corner) = blurred.refine(candidate) else {
+            continue;
+        };
+        if corners.iter().all(|other| (other.x - corner.x).hypot(other.y - corner.y) > RING_RADIUS) {
+            corners.push(corner);
+            if corners.len() == columns * rows {
+                return order(&corners, [columns, rows]);
+            }
+        }
+    }
+    None
+}
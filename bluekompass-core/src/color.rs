@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+/// Color in sRGBA with unmultiplied alpha
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Color(pub [u8; 4]);
+
+impl Color {
+    pub const BLACK: Color = Color::from_rgb(0, 0, 0);
+    pub const WHITE: Color = Color::from_rgb(255, 255, 255);
+
+    pub const fn from_rgb(r: u8, g: u8, b: u8) -> Self {
+        Self([r, g, b, 255])
+    }
+
+    pub fn to_array(self) -> [u8; 4] {
+        self.0
+    }
+}
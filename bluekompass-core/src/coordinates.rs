@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+
+use crate::point::Point;
+
+/// Orientation of the Y axis of coordinates read out and exported, the origin being the top left corner of the image
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum YAxis {
+    /// Math and CAD convention, the same as plot coordinates
+    #[default]
+    Up,
+    /// Image convention, the same as pixel coordinates
+    Down,
+}
+
+impl YAxis {
+    pub const ALL: [YAxis; 2] = [YAxis::Up, YAxis::Down];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            YAxis::Up => "Up (math)",
+            YAxis::Down => "Down (image)",
+        }
+    }
+
+    /// Coordinates of a plot point along this axis, the conversion is its own inverse
+    pub fn apply(&self, point: Point) -> Point {
+        match self {
+            YAxis::Up => point,
+            YAxis::Down => Point::new(point.x, -point.y),
+        }
+    }
+
+    /// Counterclockwise angle in degrees from the X axis, as read along this axis
+    pub fn angle(&self, degrees: f64) -> f64 {
+        match self {
+            YAxis::Up => degrees,
+            YAxis::Down => -degrees,
+        }
+    }
+}
+
+/// Position in image pixels of a plot point
+///
+/// The top left corner of the image is on the plot origin and the plot Y axis points up, so pixel `(i, j)`
+/// covers `i..i + 1` and `j..j + 1` and its center is at `(i + 0.5, j + 0.5)`.
+pub fn plot_to_pixel(point: Point) -> Point {
+    YAxis::Down.apply(point)
+}
+
+pub fn pixel_to_plot(pixel: Point) -> Point {
+    YAxis::Down.apply(pixel)
+}
+
+/// Datum frame of a part, defined in plot coordinates from features of the image
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Frame {
+    pub name: String,
+    pub origin: Point,
+    /// Direction of the X axis, counterclockwise in radians from the plot X axis
+    pub rotation: f64,
+}
+
+impl Frame {
+    /// Frame with its X axis from the first to the second point of `axis`, `None` when they are coincident
+    pub fn new(name: impl Into<String>, origin: Point, [start, end]: [Point; 2]) -> Option<Self> {
+        let (dx, dy) = (end.x - start.x, end.y - start.y);
+        (dx != 0. || dy != 0.).then(|| Self { name: name.into(), origin, rotation: dy.atan2(dx) })
+    }
+
+    /// Coordinates of a plot point in the frame, before any Y axis convention
+    pub fn to_frame(&self, point: Point) -> Point {
+        let (sin, cos) = self.rotation.sin_cos();
+        let (x, y) = (point.x - self.origin.x, point.y - self.origin.y);
+        Point::new(x * cos + y * sin, y * cos - x * sin)
+    }
+
+    pub fn from_frame(&self, point: Point) -> Point {
+        let (sin, cos) = self.rotation.sin_cos();
+        Point::new(self.origin.x + point.x * cos - point.y * sin, self.origin.y + point.x * sin + point.y * cos)
+    }
+}
@@ -0,0 +1,155 @@
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+use crate::calibration::Calibration;
+use crate::shapes::{Geometry, Shape, ShapeKind};
+
+/// Largest allowed deviations of a traced feature from its nominal one, in calibrated units and degrees
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DeviationTolerance {
+    pub position: f64,
+    pub radius: f64,
+    pub angle: f64,
+}
+
+impl Default for DeviationTolerance {
+    fn default() -> Self {
+        Self { position: 1.0, radius: 1.0, angle: 1.0 }
+    }
+}
+
+/// Largest distance between the centers of a traced shape and its nominal shape to pair them, in calibrated units
+pub const DEFAULT_PAIRING_DISTANCE: f64 = 5.0;
+
+pub(crate) fn default_pairing_distance() -> f64 {
+    DEFAULT_PAIRING_DISTANCE
+}
+
+/// Deviation of a traced shape from the nominal shape it is paired with
+#[derive(Debug, Clone)]
+pub struct Deviation {
+    pub traced_id: usize,
+    pub traced_name: String,
+    pub nominal_id: usize,
+    pub nominal_name: String,
+    pub kind: ShapeKind,
+    /// Distance between circle centers, or of the middle of a traced line from the nominal line
+    pub position: f64,
+    pub radius: Option<f64>,
+    /// Angle between lines in degrees, in `-90..=90`
+    pub angle: Option<f64>,
+    pub tolerance: DeviationTolerance,
+}
+
+impl Deviation {
+    pub fn passed(&self) -> bool {
+        self.position.abs() <= self.tolerance.position
+            && self.radius.is_none_or(|radius| radius.abs() <= self.tolerance.radius)
+            && self.angle.is_none_or(|angle| angle.abs() <= self.tolerance.angle)
+    }
+}
+
+/// Result of the comparison of traced shapes with nominal ones
+#[derive(Debug, Clone, Default)]
+pub struct Comparison {
+    pub deviations: Vec<Deviation>,
+    /// Ids of the traced shapes without nominal counterpart
+    pub unpaired_traced: Vec<usize>,
+    /// Ids of the nominal shapes that were not traced
+    pub unpaired_nominal: Vec<usize>,
+}
+
+impl Comparison {
+    pub fn failed(&self) -> usize {
+        self.deviations.iter().filter(|deviation| !deviation.passed()).count()
+    }
+}
+
+/// Deviations of a traced shape from a nominal one of the same kind, in plot units
+fn deviations(traced: &dyn Shape, nominal: &dyn Shape) -> Option<(f64, Option<f64>, Option<f64>)> {
+    match (traced.geometry()?, nominal.geometry()?) {
+        (Geometry::Line([a, b]), Geometry::Line([c, d])) => {
+            let direction = d.to_vec2() - c.to_vec2();
+            let middle = traced.center().to_vec2() - c.to_vec2();
+            let position = middle.dot(direction.rot90()).abs() / direction.length();
+            let traced_direction = b.to_vec2() - a.to_vec2();
+            let mut angle = (traced_direction.y.atan2(traced_direction.x) - direction.y.atan2(direction.x)).to_degrees();
+            // Lines have no direction
+            angle = (angle + 90.).rem_euclid(180.) - 90.;
+            Some((position, None, Some(angle)))
+        }
+        (Geometry::Circle { center, radius }, Geometry::Circle { center: nominal_center, radius: nominal_radius }) => {
+            let position = (center.to_vec2() - nominal_center.to_vec2()).length();
+            Some((position, Some(radius - nominal_radius), None))
+        }
+        _ => None,
+    }
+}
+
+/// Pair every traced shape with the nearest nominal shape of the same kind and measure their deviations
+///
+/// Pairs are made from the closest to the farthest so that every nominal shape is paired at most once, and only
+/// between shapes whose centers are at most `pairing_distance` apart in calibrated units, farther shapes are left
+/// unpaired. Nominal shapes without a tolerance of their own use `default_tolerance`.
+pub fn compare(
+    traced: &[&dyn Shape],
+    nominal: &[&dyn Shape],
+    default_tolerance: DeviationTolerance,
+    pairing_distance: f64,
+    calibration: &Calibration,
+) -> Comparison {
+    let mut candidates: Vec<(usize, usize, f64)> = traced.iter()
+        .enumerate()
+        .flat_map(|(traced_index, traced_shape)| {
+            nominal.iter()
+                .enumerate()
+                .filter(|(_, nominal_shape)| nominal_shape.kind() == traced_shape.kind())
+                .filter(|(_, nominal_shape)| traced_shape.is_valid() && nominal_shape.is_valid())
+                .map(move |(nominal_index, nominal_shape)| {
+                    let distance = (traced_shape.center().to_vec2() - nominal_shape.center().to_vec2()).length();
+                    (traced_index, nominal_index, distance)
+                })
+        })
+        .filter(|&(_, _, distance)| calibration.length(distance) <= pairing_distance)
+        .collect();
+    candidates.sort_by(|(_, _, a), (_, _, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+    let mut traced_paired = vec![false; traced.len()];
+    let mut nominal_paired = vec![false; nominal.len()];
+    let mut comparison = Comparison::default();
+    for (traced_index, nominal_index, _) in candidates {
+        if traced_paired[traced_index] || nominal_paired[nominal_index] {
+            continue;
+        }
+        let (traced_shape, nominal_shape) = (traced[traced_index], nominal[nominal_index]);
+        let Some((position, radius, angle)) = deviations(traced_shape, nominal_shape) else {
+            continue;
+        };
+        traced_paired[traced_index] = true;
+        nominal_paired[nominal_index] = true;
+        comparison.deviations.push(Deviation {
+            traced_id: traced_shape.properties().id,
+            traced_name: traced_shape.properties().name.clone(),
+            nominal_id: nominal_shape.properties().id,
+            nominal_name: nominal_shape.properties().name.clone(),
+            kind: traced_shape.kind(),
+            position: calibration.length(position),
+            radius: radius.map(|radius| calibration.length(radius)),
+            angle,
+            tolerance: nominal_shape.properties().deviation_tolerance.unwrap_or(default_tolerance),
+        });
+    }
+    comparison.deviations.sort_by_key(|deviation| deviation.traced_id);
+
+    let unpaired = |shapes: &[&dyn Shape], paired: &[bool]| -> Vec<usize> {
+        shapes.iter()
+            .zip(paired)
+            .filter(|(_, &paired)| !paired)
+            .map(|(shape, _)| shape.properties().id)
+            .collect()
+    };
+    comparison.unpaired_traced = unpaired(traced, &traced_paired);
+    comparison.unpaired_nominal = unpaired(nominal, &nominal_paired);
+    comparison
+}
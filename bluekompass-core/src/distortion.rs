@@ -0,0 +1,199 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::homography::Homography;
+use crate::maths::solve;
+use crate::point::Point;
+
+/// Fixed-point iterations to invert the distortion
+const UNDISTORT_ITERATIONS: usize = 20;
+
+/// Most steps of the fit of the coefficients
+const MAX_ITERATIONS: usize = 100;
+
+/// Step of the numerical derivatives, in normalized coordinates
+const DERIVATIVE_STEP: f64 = 1e-7;
+
+/// Radial and tangential lens distortion (Brown–Conrady) around the center of the image
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Distortion {
+    /// Optical center in pixels
+    pub center: Point,
+    /// Length in pixels the coordinates are divided by before the coefficients apply
+    pub focal: f64,
+    /// k1 and k2
+    pub radial: [f64; 2],
+    /// p1 and p2
+    pub tangential: [f64; 2],
+}
+
+impl Distortion {
+    /// No distortion, for images of the given size
+    pub fn none([width, height]: [usize; 2]) -> Self {
+        let (width, height) = (width as f64, height as f64);
+        Self {
+            center: Point::new(0.5 * width, 0.5 * height),
+            focal: 0.5 * width.max(height).max(1.),
+            radial: [0.; 2],
+            tangential: [0.; 2],
+        }
+    }
+
+    fn distort_normalized(&self, x: f64, y: f64) -> (f64, f64) {
+        let [k1, k2] = self.radial;
+        let [p1, p2] = self.tangential;
+        let r2 = x * x + y * y;
+        let radial = 1. + k1 * r2 + k2 * r2 * r2;
+        (
+            x * radial + 2. * p1 * x * y + p2 * (r2 + 2. * x * x),
+            y * radial + p1 * (r2 + 2. * y * y) + 2. * p2 * x * y,
+        )
+    }
+
+    /// Where the lens shows a pixel of the undistorted image
+    pub fn distort(&self, pixel: Point) -> Point {
+        let (x, y) = self.distort_normalized((pixel.x - self.center.x) / self.focal, (pixel.y - self.center.y) / self.focal);
+        Point::new(self.center.x + x * self.focal, self.center.y + y * self.focal)
+    }
+
+    /// Pixel of the undistorted image shown at a pixel of the lens image, inverse of `distort`
+    pub fn undistort(&self, pixel: Point) -> Point {
+        let target = ((pixel.x - self.center.x) / self.focal, (pixel.y - self.center.y) / self.focal);
+        let (mut x, mut y) = target;
+        for _ in 0..UNDISTORT_ITERATIONS {
+            let (distorted_x, distorted_y) = self.distort_normalized(x, y);
+            x -= distorted_x - target.0;
+            y -= distorted_y - target.1;
+        }
+        Point::new(self.center.x + x * self.focal, self.center.y + y * self.focal)
+    }
+}
+
+/// Distortion of a camera and its lens, saved to correct the images it takes (`.bkc`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CameraProfile {
+    pub name: String,
+    /// Size of the calibration images, the distortion holds for images of this size
+    pub image_size: [usize; 2],
+    pub distortion: Distortion,
+    /// Root mean square distance in pixels between the detected corners and the fitted model
+    pub residual: f64,
+    /// Number of calibration images
+    pub images: usize,
+}
+
+impl CameraProfile {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let profile: Self = serde_json::from_str(&fs::read_to_string(path)?)?;
+        let distortion = &profile.distortion;
+        let coefficients = [distortion.center.x, distortion.center.y, distortion.focal]
+            .into_iter()
+            .chain(distortion.radial)
+            .chain(distortion.tangential);
+        if distortion.focal <= 0. || !coefficients.into_iter().all(f64::is_finite) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid distortion coefficients"));
+        }
+        Ok(profile)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// Distortion fitted to the inner corners of a flat checkerboard seen in images of the given size, with its
+/// root mean square residual in pixels
+///
+/// Each view holds the `columns × rows` corners row by row. Once undistorted the corners of a view are the
+/// grid seen in perspective, the coefficients and one homography per view are fitted by Levenberg–Marquardt.
+pub fn calibrate(views: &[Vec<Point>], [columns, rows]: [usize; 2], size: [usize; 2]) -> Option<(Distortion, f64)> {
+    let count = columns * rows;
+    if columns < 3 || rows < 3 || views.is_empty() || views.iter().any(|view| view.len() != count) {
+        return None;
+    }
+    let base = Distortion::none(size);
+    let normalized: Vec<Vec<Point>> = views.iter()
+        .map(|view| {
+            view.iter()
+                .map(|point| Point::new((point.x - base.center.x) / base.focal, (point.y - base.center.y) / base.focal))
+                .collect()
+        })
+        .collect();
+    let grid: Vec<Point> = (0..count).map(|index| Point::new((index % columns) as f64, (index / columns) as f64)).collect();
+
+    // Coefficients then the eight free entries of the homography of each view, from the corners of the grid
+    let mut parameters = vec![0.; 4];
+    let outer = [0, columns - 1, count - 1, count - columns];
+    for view in &normalized {
+        let homography = Homography::from_points(outer.map(|index| grid[index]), outer.map(|index| view[index]))?;
+        parameters.extend_from_slice(&homography.0[..8]);
+    }
+
+    let residuals = |parameters: &[f64]| -> Option<Vec<f64>> {
+        let distortion = Distortion {
+            radial: [parameters[0], parameters[1]],
+            tangential: [parameters[2], parameters[3]],
+            ..base
+        };
+        let mut residuals = Vec::with_capacity(2 * count * views.len());
+        for (view, homography) in normalized.iter().zip(parameters[4..].chunks(8)) {
+            let mut matrix = [1.; 9];
+            matrix[..8].copy_from_slice(homography);
+            for (corner, observed) in grid.iter().zip(view) {
+                let ideal = Homography(matrix).apply(*corner)?;
+                let (x, y) = distortion.distort_normalized(ideal.x, ideal.y);
+                residuals.extend([x - observed.x, y - observed.y]);
+            }
+        }
+        Some(residuals)
+    };
+    let cost = |residuals: &[f64]| residuals.iter().map(|residual| residual * residual).sum::<f64>();
+
+    let mut current = residuals(&parameters)?;
+    let mut lambda = 1e-3;
+    for _ in 0..MAX_ITERATIONS {
+        let jacobian: Vec<Vec<f64>> = (0..parameters.len())
+            .map(|column| {
+                let mut moved = parameters.clone();
+                moved[column] += DERIVATIVE_STEP;
+                let moved = residuals(&moved)?;
+                Some(moved.iter().zip(&current).map(|(moved, current)| (moved - current) / DERIVATIVE_STEP).collect())
+            })
+            .collect::<Option<_>>()?;
+        let normal: Vec<Vec<f64>> = jacobian.iter()
+            .map(|a| jacobian.iter().map(|b| a.iter().zip(b).map(|(a, b)| a * b).sum()).collect())
+            .collect();
+        let gradient: Vec<f64> = jacobian.iter().map(|a| -a.iter().zip(&current).map(|(a, r)| a * r).sum::<f64>()).collect();
+
+        let previous = cost(&current);
+        let mut improved = false;
+        while lambda < 1e12 {
+            let mut damped = normal.clone();
+            for (index, row) in damped.iter_mut().enumerate() {
+                row[index] += lambda * normal[index][index].max(1e-12);
+            }
+            let candidate = solve(damped, gradient.clone())
+                .map(|step| parameters.iter().zip(step).map(|(parameter, step)| parameter + step).collect::<Vec<_>>());
+            if let Some((candidate, moved)) = candidate.and_then(|candidate| residuals(&candidate).map(|moved| (candidate, moved))) {
+                if cost(&moved) < previous {
+                    parameters = candidate;
+                    current = moved;
+                    lambda = (lambda / 10.).max(1e-12);
+                    improved = true;
+                    break;
+                }
+            }
+            lambda *= 10.;
+        }
+        if !improved || previous - cost(&current) < 1e-14 * previous {
+            break;
+        }
+    }
+
+    let distortion = Distortion { radial: [parameters[0], parameters[1]], tangential: [parameters[2], parameters[3]], ..base };
+    let residual = (cost(&current) / (count * views.len()) as f64).sqrt() * base.focal;
+    Some((distortion, residual))
+}
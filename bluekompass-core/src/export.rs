@@ -0,0 +1,7 @@
+mod svg;
+mod dxf;
+mod table;
+
+pub use svg::{to_svg, SvgImage};
+pub use dxf::to_dxf;
+pub use table::{delimited_field, shape_rows, to_csv, to_json, to_tsv, ShapeRow};
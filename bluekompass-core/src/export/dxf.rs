@@ -0,0 +1,118 @@
+use std::fmt::Write;
+
+use crate::color::Color;
+use crate::calibration::Calibration;
+use crate::layers::Layer;
+use crate::shapes::{Geometry, Shape};
+
+/// Characters that are not allowed in DXF layer names
+const FORBIDDEN: &[char] = &['<', '>', '/', '\\', '"', ':', ';', '?', '*', '|', '=', ',', '`'];
+
+fn layer_name(layer: &Layer) -> String {
+    layer.name.replace(FORBIDDEN, "_")
+}
+
+/// Nearest AutoCAD Color Index among the seven standard colors
+fn color_index(color: Color) -> i32 {
+    const STANDARD: [(i32, [u8; 3]); 7] = [
+        (1, [255, 0, 0]),
+        (2, [255, 255, 0]),
+        (3, [0, 255, 0]),
+        (4, [0, 255, 255]),
+        (5, [0, 0, 255]),
+        (6, [255, 0, 255]),
+        (7, [255, 255, 255]),
+    ];
+    let [r, g, b, _] = color.to_array();
+    if r.max(g).max(b) < 64 {
+        // Black is displayed as white on dark backgrounds
+        return 7;
+    }
+    STANDARD.iter()
+        .min_by_key(|(_, [sr, sg, sb])| {
+            [(r, sr), (g, sg), (b, sb)].iter()
+                .map(|(c, s)| (*c as i32 - **s as i32).pow(2))
+                .sum::<i32>()
+        })
+        .map_or(7, |(index, _)| *index)
+}
+
+fn group(dxf: &mut String, code: i32, value: impl std::fmt::Display) {
+    let _ = write!(dxf, "{code}\n{value}\n");
+}
+
+/// Color of an entity overriding the one of its layer
+fn entity_color(dxf: &mut String, color: Option<Color>) {
+    if let Some(color) = color {
+        let [r, g, b, _] = color.to_array();
+        group(dxf, 62, color_index(color));
+        group(dxf, 420, true_color(r, g, b));
+    }
+}
+
+fn true_color(r: u8, g: u8, b: u8) -> u32 {
+    (r as u32) << 16 | (g as u32) << 8 | b as u32
+}
+
+/// Export shapes as DXF with one DXF layer per layer, in pixels in the active frame of the calibration
+pub fn to_dxf(shapes: &[Box<dyn Shape>], layers: &[Layer], calibration: &Calibration) -> String {
+    let mut dxf = String::new();
+
+    // Layer table
+    group(&mut dxf, 0, "SECTION");
+    group(&mut dxf, 2, "TABLES");
+    group(&mut dxf, 0, "TABLE");
+    group(&mut dxf, 2, "LAYER");
+    group(&mut dxf, 70, layers.len());
+    for layer in layers {
+        let [r, g, b, _] = layer.color.to_array();
+        let color = color_index(layer.color);
+        group(&mut dxf, 0, "LAYER");
+        group(&mut dxf, 2, layer_name(layer));
+        group(&mut dxf, 70, if layer.locked { 4 } else { 0 });
+        // A negative color index turns the layer off
+        group(&mut dxf, 62, if layer.visible { color } else { -color });
+        group(&mut dxf, 6, "CONTINUOUS");
+        group(&mut dxf, 420, true_color(r, g, b));
+    }
+    group(&mut dxf, 0, "ENDTAB");
+    group(&mut dxf, 0, "ENDSEC");
+
+    // Entities
+    group(&mut dxf, 0, "SECTION");
+    group(&mut dxf, 2, "ENTITIES");
+    for shape in shapes.iter().filter(|shape| shape.properties().visible) {
+        let Some(geometry) = shape.geometry() else {
+            continue;
+        };
+        let layer = layer_name(&layers[shape.properties().layer]);
+        let color = shape.properties().style.color;
+        match geometry {
+            Geometry::Line(points) => {
+                let [a, b] = points.map(|point| calibration.frame_point(point));
+                group(&mut dxf, 0, "LINE");
+                group(&mut dxf, 8, layer);
+                entity_color(&mut dxf, color);
+                group(&mut dxf, 10, a.x);
+                group(&mut dxf, 20, a.y);
+                group(&mut dxf, 30, 0.0);
+                group(&mut dxf, 11, b.x);
+                group(&mut dxf, 21, b.y);
+                group(&mut dxf, 31, 0.0);
+            }
+            Geometry::Circle { center, radius } => {
+                let center = calibration.frame_point(center);
+                group(&mut dxf, 0, "CIRCLE");
+                group(&mut dxf, 8, layer);
+                entity_color(&mut dxf, color);
+                group(&mut dxf, 10, center.x);
+                group(&mut dxf, 20, center.y);
+                group(&mut dxf, 30, 0.0);
+                group(&mut dxf, 40, radius);
+            }
+        }
+    }
+    group(&mut dxf, 0, "ENDSEC");
+    group(&mut dxf, 0, "EOF");
+    dxf
+}
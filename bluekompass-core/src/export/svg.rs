@@ -0,0 +1,117 @@
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::color::Color;
+use crate::coordinates::plot_to_pixel;
+use crate::layers::Layer;
+use crate::shapes::{Geometry, Shape};
+use crate::style::Style;
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn stroke(color: Color) -> String {
+    let [r, g, b, a] = color.to_array();
+    format!(r##"stroke="#{r:02x}{g:02x}{b:02x}" stroke-opacity="{:.3}""##, a as f32 / 255.)
+}
+
+/// Attributes overriding the layer stroke
+fn style_attributes(style: &Style, layer: &Layer) -> String {
+    let mut attributes = String::new();
+    if let Some(color) = style.color {
+        let _ = write!(attributes, " {}", stroke(color));
+    }
+    if let Some(width) = style.width {
+        let _ = write!(attributes, r#" stroke-width="{width}""#);
+    }
+    if let Some([dash, gap]) = style.dash_pattern(layer) {
+        let _ = write!(attributes, r#" stroke-dasharray="{dash} {gap}""#);
+    }
+    attributes
+}
+
+/// Background image of an SVG export, its pixels must be those the shapes are drawn on
+#[derive(Debug, Clone, Copy)]
+pub enum SvgImage<'a> {
+    /// Image file linked as it is
+    File(&'a Path),
+    /// PNG file embedded in the SVG, for pixels that were rotated, flipped or corrected
+    Png(&'a [u8]),
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (index, &byte)| group | (byte as u32) << (16 - 8 * index));
+        for index in 0..4 {
+            if index <= chunk.len() {
+                text.push(BASE64[(group >> (18 - 6 * index) & 63) as usize] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
+
+/// Export shapes as SVG with one group per layer, in image pixel coordinates
+pub fn to_svg(shapes: &[Box<dyn Shape>], layers: &[Layer], size: [usize; 2], image: Option<SvgImage>) -> String {
+    let [width, height] = size.map(|length| length as f64);
+    let to_image = |point| {
+        let pixel = plot_to_pixel(point);
+        (pixel.x, pixel.y)
+    };
+
+    let mut svg = String::new();
+    let _ = writeln!(svg, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+    );
+    if let Some(image) = image {
+        let href = match image {
+            SvgImage::File(path) => escape(&path.to_string_lossy()),
+            SvgImage::Png(png) => format!("data:image/png;base64,{}", base64(png)),
+        };
+        let _ = writeln!(svg, r#"  <image xlink:href="{href}" x="0" y="0" width="{width}" height="{height}"/>"#);
+    }
+
+    for (index, layer) in layers.iter().enumerate() {
+        let _ = writeln!(
+            svg,
+            r#"  <g id="layer{index}" inkscape:groupmode="layer" inkscape:label="{}" fill="none" {} stroke-width="{}"{}>"#,
+            escape(&layer.name),
+            stroke(layer.color),
+            layer.width,
+            if layer.visible { "" } else { r#" style="display:none""# },
+        );
+        let layer_shapes = shapes.iter()
+            .filter(|shape| shape.properties().layer == index && shape.properties().visible);
+        for shape in layer_shapes {
+            let Some(geometry) = shape.geometry() else {
+                continue;
+            };
+            let name = escape(&shape.properties().name);
+            let attributes = style_attributes(&shape.properties().style, layer);
+            match geometry {
+                Geometry::Line([a, b]) => {
+                    let ((x1, y1), (x2, y2)) = (to_image(a), to_image(b));
+                    let _ = writeln!(svg, r#"    <line id="{name}" x1="{x1}" y1="{y1}" x2="{x2}" y2="{y2}"{attributes}/>"#);
+                }
+                Geometry::Circle { center, radius } => {
+                    let (cx, cy) = to_image(center);
+                    let _ = writeln!(svg, r#"    <circle id="{name}" cx="{cx}" cy="{cy}" r="{radius}"{attributes}/>"#);
+                }
+            }
+        }
+        let _ = writeln!(svg, "  </g>");
+    }
+    let _ = writeln!(svg, "</svg>");
+    svg
+}
@@ -0,0 +1,111 @@
+use std::fmt::Write;
+
+use serde::Serialize;
+
+use crate::calibration::Calibration;
+use crate::layers::Layer;
+use crate::shapes::{Geometry, Shape};
+
+const COLUMNS: [&str; 11] = ["id", "type", "name", "layer", "points", "length", "angle", "center", "radius", "area", "unit"];
+
+/// Defining points and derived values of a shape, in calibrated units
+#[derive(Debug, Clone, Serialize)]
+pub struct ShapeRow {
+    pub id: usize,
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub name: String,
+    pub layer: String,
+    pub points: Vec<[f64; 2]>,
+    pub length: Option<f64>,
+    /// Angle in degrees from the X axis
+    pub angle: Option<f64>,
+    pub center: Option<[f64; 2]>,
+    pub radius: Option<f64>,
+    pub area: Option<f64>,
+    pub unit: String,
+}
+
+/// One row per shape, derived values are left empty for degenerate shapes
+pub fn shape_rows(shapes: &[Box<dyn Shape>], layers: &[Layer], calibration: &Calibration) -> Vec<ShapeRow> {
+    shapes.iter()
+        .map(|shape| {
+            let properties = shape.properties();
+            let mut row = ShapeRow {
+                id: properties.id,
+                kind: shape.kind().name(),
+                name: properties.name.clone(),
+                layer: layers.get(properties.layer).map_or_else(String::new, |layer| layer.name.clone()),
+                points: shape.as_slice().iter().map(|&point| calibration.point(point).into()).collect(),
+                length: None,
+                angle: None,
+                center: None,
+                radius: None,
+                area: None,
+                unit: calibration.unit.clone(),
+            };
+            match shape.geometry() {
+                Some(Geometry::Line([a, b])) => {
+                    row.length = Some(calibration.length((b.x - a.x).hypot(b.y - a.y)));
+                    row.angle = Some(calibration.angle((b.y - a.y).atan2(b.x - a.x).to_degrees()));
+                    row.center = Some(calibration.point(shape.center()).into());
+                }
+                Some(Geometry::Circle { center, radius }) => {
+                    row.center = Some(calibration.point(center).into());
+                    row.radius = Some(calibration.length(radius));
+                    row.area = Some(calibration.area(std::f64::consts::PI * radius * radius));
+                }
+                None => {}
+            }
+            row
+        })
+        .collect()
+}
+
+/// Quote a field containing the separator, a quote or a line break
+pub fn delimited_field(field: &str, separator: char) -> String {
+    if field.contains([separator, '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn to_delimited(rows: &[ShapeRow], separator: char) -> String {
+    let optional = |value: Option<f64>| value.map_or_else(String::new, |value| value.to_string());
+    let coordinates = |[x, y]: [f64; 2]| format!("{x} {y}");
+
+    let mut text = COLUMNS.join(&separator.to_string());
+    text.push('\n');
+    for row in rows {
+        let fields = [
+            row.id.to_string(),
+            delimited_field(row.kind, separator),
+            delimited_field(&row.name, separator),
+            delimited_field(&row.layer, separator),
+            row.points.iter().copied().map(coordinates).collect::<Vec<_>>().join("; "),
+            optional(row.length),
+            optional(row.angle),
+            row.center.map_or_else(String::new, coordinates),
+            optional(row.radius),
+            optional(row.area),
+            delimited_field(&row.unit, separator),
+        ];
+        let _ = writeln!(text, "{}", fields.join(&separator.to_string()));
+    }
+    text
+}
+
+/// Rows as comma-separated values with a header line, points are written as `x y` pairs separated by `;`
+pub fn to_csv(rows: &[ShapeRow]) -> String {
+    to_delimited(rows, ',')
+}
+
+/// Rows as tab-separated values, as pasted in spreadsheets
+pub fn to_tsv(rows: &[ShapeRow]) -> String {
+    to_delimited(rows, '\t')
+}
+
+pub fn to_json(rows: &[ShapeRow]) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(rows)
+}
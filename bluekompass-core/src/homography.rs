@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+
+use crate::maths::solve;
+use crate::point::Point;
+
+/// Projective transform of the plane, a row-major 3×3 matrix acting on `(x, y, 1)`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Homography(pub [f64; 9]);
+
+impl Homography {
+    /// Homography moving each `from` point onto its `to` point, `None` when three points are aligned
+    pub fn from_points(from: [Point; 4], to: [Point; 4]) -> Option<Self> {
+        // h33 = 1, two equations per point pair
+        let mut a = [[0.; 8]; 8];
+        let mut b = [0.; 8];
+        for (index, (p, q)) in from.iter().zip(&to).enumerate() {
+            a[2 * index] = [p.x, p.y, 1., 0., 0., 0., -p.x * q.x, -p.y * q.x];
+            b[2 * index] = q.x;
+            a[2 * index + 1] = [0., 0., 0., p.x, p.y, 1., -p.x * q.y, -p.y * q.y];
+            b[2 * index + 1] = q.y;
+        }
+        let h = solve(a.map(Vec::from).to_vec(), b.to_vec())?;
+        let homography = Self([h[0], h[1], h[2], h[3], h[4], h[5], h[6], h[7], 1.]);
+        homography.0.iter().all(|value| value.is_finite()).then_some(homography)
+    }
+
+    /// Homography moving the `from` points onto the `to` points, fitted by least squares when there are more than four
+    ///
+    /// Both sets are centered and scaled first so that the fit does not depend on where the points are. `None` with
+    /// fewer than four pairs or when they do not determine a homography.
+    pub fn fit(from: &[Point], to: &[Point]) -> Option<Self> {
+        if from.len() != to.len() || from.len() < 4 {
+            return None;
+        }
+        let (normalize_from, normalize_to) = (normalizing(from)?, normalizing(to)?);
+        let mut normal = vec![vec![0.; 8]; 8];
+        let mut right = vec![0.; 8];
+        for (&p, &q) in from.iter().zip(to) {
+            let (p, q) = (normalize_from.apply(p)?, normalize_to.apply(q)?);
+            let equations = [
+                ([p.x, p.y, 1., 0., 0., 0., -p.x * q.x, -p.y * q.x], q.x),
+                ([0., 0., 0., p.x, p.y, 1., -p.x * q.y, -p.y * q.y], q.y),
+            ];
+            for (terms, value) in equations {
+                for (row, term) in terms.iter().enumerate() {
+                    for (column, other) in terms.iter().enumerate() {
+                        normal[row][column] += term * other;
+                    }
+                    right[row] += term * value;
+                }
+            }
+        }
+        let h = solve(normal, right)?;
+        let normalized = Self([h[0], h[1], h[2], h[3], h[4], h[5], h[6], h[7], 1.]);
+        let product = normalize_to.inverse()?.then(&normalized).then(&normalize_from).0;
+        if product[8].abs() < 1e-12 {
+            return None;
+        }
+        let homography = Self(product.map(|value| value / product[8]));
+        homography.0.iter().all(|value| value.is_finite()).then_some(homography)
+    }
+
+    /// Homography applying `other` then this one
+    fn then(&self, other: &Self) -> Self {
+        let (a, b) = (&self.0, &other.0);
+        Self(std::array::from_fn(|index| {
+            let (row, column) = (index / 3, index % 3);
+            (0..3).map(|k| a[3 * row + k] * b[3 * k + column]).sum()
+        }))
+    }
+
+    /// Image of a point, `None` for a point sent to infinity
+    pub fn apply(&self, point: Point) -> Option<Point> {
+        let h = &self.0;
+        let w = h[6] * point.x + h[7] * point.y + h[8];
+        (w.abs() > 1e-12).then(|| {
+            Point::new(
+                (h[0] * point.x + h[1] * point.y + h[2]) / w,
+                (h[3] * point.x + h[4] * point.y + h[5]) / w,
+            )
+        })
+    }
+
+    pub fn inverse(&self) -> Option<Self> {
+        let [a, b, c, d, e, f, g, h, i] = self.0;
+        let cofactors = [e * i - f * h, c * h - b * i, b * f - c * e, f * g - d * i, a * i - c * g, c * d - a * f, d * h - e * g, b * g - a * h, a * e - b * d];
+        let determinant = a * cofactors[0] + b * cofactors[3] + c * cofactors[6];
+        (determinant.abs() > 1e-18).then(|| Self(cofactors.map(|cofactor| cofactor / determinant)))
+    }
+
+    /// Root mean square distance between the images of the `from` points and the `to` points
+    pub fn residual(&self, from: &[Point], to: &[Point]) -> Option<f64> {
+        let squares = from.iter()
+            .zip(to)
+            .map(|(&p, q)| self.apply(p).map(|p| (p.x - q.x).powi(2) + (p.y - q.y).powi(2)))
+            .collect::<Option<Vec<f64>>>()?;
+        (!squares.is_empty()).then(|| (squares.iter().sum::<f64>() / squares.len() as f64).sqrt())
+    }
+}
+
+/// Similarity moving the centroid of the points to the origin, at a mean distance of √2 from it
+fn normalizing(points: &[Point]) -> Option<Homography> {
+    let count = points.len() as f64;
+    let (x, y) = points.iter().fold((0., 0.), |(x, y), point| (x + point.x / count, y + point.y / count));
+    let distance = points.iter().map(|point| (point.x - x).hypot(point.y - y)).sum::<f64>() / count;
+    let scale = std::f64::consts::SQRT_2 / distance;
+    scale.is_finite().then_some(Homography([scale, 0., -scale * x, 0., scale, -scale * y, 0., 0., 1.]))
+}
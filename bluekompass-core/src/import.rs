@@ -0,0 +1,3 @@
+mod dxf;
+
+pub use dxf::{from_dxf, DxfImport};
@@ -0,0 +1,194 @@
+use std::io;
+
+use crate::point::Point;
+use crate::shapes::{build_shape, Shape, ShapeKind};
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Group codes and values of an entity, in file order
+struct Entity<'a> {
+    kind: &'a str,
+    groups: Vec<(i32, &'a str)>,
+}
+
+impl Entity<'_> {
+    fn values(&self, code: i32) -> impl Iterator<Item = f64> + '_ {
+        self.groups.iter()
+            .filter(move |(group, _)| *group == code)
+            .filter_map(|(_, value)| value.parse().ok())
+    }
+
+    fn value(&self, code: i32) -> Option<f64> {
+        self.values(code).next()
+    }
+
+    fn point(&self, x_code: i32) -> Option<Point> {
+        Some(Point::new(self.value(x_code)?, self.value(x_code + 10)?))
+    }
+
+    /// Point of the circle of the entity at `angle` degrees
+    fn point_on_circle(&self, angle: f64) -> Option<Point> {
+        let center = self.point(10)?;
+        let radius = self.value(40)?;
+        let (sin, cos) = angle.to_radians().sin_cos();
+        Some(Point::new(center.x + radius * cos, center.y + radius * sin))
+    }
+
+    /// Vertices of a polyline with the bulge of the segment that starts at each of them
+    fn vertices(&self) -> Vec<(Point, f64)> {
+        let mut vertices: Vec<(Point, f64)> = Vec::new();
+        for &(code, value) in &self.groups {
+            let Ok(value) = value.parse::<f64>() else {
+                continue;
+            };
+            match (code, vertices.last_mut()) {
+                (10, _) => vertices.push((Point::new(value, 0.), 0.)),
+                (20, Some((point, _))) => point.y = value,
+                (42, Some((_, bulge))) => *bulge = value,
+                _ => (),
+            }
+        }
+        vertices
+    }
+
+    /// Shapes of the entity with the number of arcs drawn as straight segments, `None` for a kind that is not imported
+    fn shapes(&self) -> Option<(Vec<Box<dyn Shape>>, usize)> {
+        let lines = |points: &[Point]| -> Vec<Box<dyn Shape>> {
+            points.windows(2)
+                .filter_map(|segment| build_shape(ShapeKind::Line, segment))
+                .collect()
+        };
+
+        let (shapes, approximated) = match self.kind {
+            "LINE" => {
+                let shapes = self.point(10)
+                    .zip(self.point(11))
+                    .map_or_else(Vec::new, |(start, end)| lines(&[start, end]));
+                (shapes, 0)
+            }
+            "CIRCLE" => {
+                let points = [0., 120., 240.].iter()
+                    .map(|&angle| self.point_on_circle(angle))
+                    .collect::<Option<Vec<_>>>();
+                (points.and_then(|points| build_shape(ShapeKind::Circle, &points)).into_iter().collect(), 0)
+            }
+            "ARC" => {
+                let (Some(center), Some(radius)) = (self.point(10), self.value(40)) else {
+                    return Some((Vec::new(), 0));
+                };
+                let start = self.value(50).unwrap_or(0.);
+                let mut sweep = (self.value(51).unwrap_or(360.) - start).rem_euclid(360.);
+                if sweep == 0. {
+                    sweep = 360.;
+                }
+                (lines(&arc_points(center, radius, start, sweep)), 1)
+            }
+            "LWPOLYLINE" => {
+                let mut vertices = self.vertices();
+                let closed = self.value(70).is_some_and(|flags| flags as i32 & 1 == 1);
+                if closed && vertices.len() > 2 {
+                    vertices.push((vertices[0].0, 0.));
+                }
+                let mut points: Vec<Point> = vertices.first().map(|(point, _)| *point).into_iter().collect();
+                let mut approximated = 0;
+                for segment in vertices.windows(2) {
+                    let [(start, bulge), (end, _)] = [segment[0], segment[1]];
+                    if let Some((center, radius, start_angle, sweep)) = bulge_arc(start, end, bulge) {
+                        // The end is kept as it is rather than computed again
+                        let arc = arc_points(center, radius, start_angle, sweep);
+                        points.extend(&arc[1..arc.len() - 1]);
+                        approximated += 1;
+                    }
+                    points.push(end);
+                }
+                (lines(&points), approximated)
+            }
+            _ => return None,
+        };
+        Some((shapes.into_iter().filter(|shape| shape.is_valid()).collect(), approximated))
+    }
+}
+
+/// Largest angle in degrees of the straight segments arcs are drawn with, they stray from the arc by less than
+/// 0.1 % of its radius
+const ARC_STEP: f64 = 5.;
+
+/// Points along an arc from `start` degrees, counterclockwise for a positive `sweep` in degrees, both ends included
+fn arc_points(center: Point, radius: f64, start: f64, sweep: f64) -> Vec<Point> {
+    let segments = (sweep.abs() / ARC_STEP).ceil().max(1.) as usize;
+    (0..=segments)
+        .map(|index| {
+            let (sin, cos) = (start + sweep * index as f64 / segments as f64).to_radians().sin_cos();
+            Point::new(center.x + radius * cos, center.y + radius * sin)
+        })
+        .collect()
+}
+
+/// Center, radius, start angle and sweep in degrees of the arc of a polyline segment, `None` for a straight one
+///
+/// The bulge is the tangent of a quarter of the sweep, positive counterclockwise.
+fn bulge_arc(start: Point, end: Point, bulge: f64) -> Option<(Point, f64, f64, f64)> {
+    let (dx, dy) = (end.x - start.x, end.y - start.y);
+    let chord = dx.hypot(dy);
+    if bulge == 0. || !bulge.is_finite() || chord == 0. {
+        return None;
+    }
+    let sweep = 4. * bulge.atan();
+    // Signed distance from the middle of the chord to the center, on the left of the chord
+    let offset = 0.5 * chord / (0.5 * sweep).tan();
+    let center = Point::new(0.5 * (start.x + end.x) - dy / chord * offset, 0.5 * (start.y + end.y) + dx / chord * offset);
+    let radius = (start.x - center.x).hypot(start.y - center.y);
+    let start_angle = (start.y - center.y).atan2(start.x - center.x).to_degrees();
+    Some((center, radius, start_angle, sweep.to_degrees()))
+}
+
+/// Shapes imported from a DXF file, with what could not be imported as drawn
+pub struct DxfImport {
+    pub shapes: Vec<Box<dyn Shape>>,
+    /// Arcs and bulged polyline segments, drawn as straight segments close to them
+    pub approximated: usize,
+    /// Kinds of the entities that are not imported, one per entity
+    pub skipped: Vec<String>,
+}
+
+/// Import the LINE, CIRCLE, ARC and LWPOLYLINE entities of a DXF file as shapes, in drawing coordinates
+///
+/// Arcs and bulged polyline segments become straight segments that follow them.
+pub fn from_dxf(dxf: &str) -> io::Result<DxfImport> {
+    let mut groups = Vec::new();
+    let mut lines = dxf.lines();
+    while let Some(code) = lines.next() {
+        let value = lines.next().ok_or_else(|| invalid(format!("missing value of group {}", code.trim())))?;
+        let code = code.trim().parse::<i32>().map_err(|_| invalid(format!("invalid group code {}", code.trim())))?;
+        groups.push((code, value.trim()));
+    }
+
+    let mut entities: Vec<Entity> = Vec::new();
+    let mut in_entities = false;
+    for (index, &(code, value)) in groups.iter().enumerate() {
+        match (code, value) {
+            (0, "SECTION") => in_entities = groups.get(index + 1) == Some(&(2, "ENTITIES")),
+            (0, "ENDSEC") => in_entities = false,
+            (0, kind) if in_entities => entities.push(Entity { kind, groups: Vec::new() }),
+            group => {
+                if let Some(entity) = entities.last_mut().filter(|_| in_entities) {
+                    entity.groups.push(group);
+                }
+            }
+        }
+    }
+
+    let mut import = DxfImport { shapes: Vec::new(), approximated: 0, skipped: Vec::new() };
+    for entity in &entities {
+        match entity.shapes() {
+            Some((shapes, approximated)) => {
+                import.shapes.extend(shapes);
+                import.approximated += approximated;
+            }
+            None => import.skipped.push(entity.kind.to_string()),
+        }
+    }
+    Ok(import)
+}
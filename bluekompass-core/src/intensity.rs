@@ -0,0 +1,124 @@
+use crate::adjustments::luma;
+use crate::point::Point;
+
+/// Part of an image, in pixel coordinates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Region {
+    All,
+    Rectangle { min: Point, max: Point },
+    Disc { center: Point, radius: f64 },
+}
+
+impl Region {
+    /// Whether the center of a pixel is in the region
+    fn contains(&self, x: usize, y: usize) -> bool {
+        let (x, y) = (x as f64 + 0.5, y as f64 + 0.5);
+        match *self {
+            Region::All => true,
+            Region::Rectangle { min, max } => (min.x..=max.x).contains(&x) && (min.y..=max.y).contains(&y),
+            Region::Disc { center, radius } => (x - center.x).hypot(y - center.y) <= radius,
+        }
+    }
+}
+
+/// Number of pixels of every value, by channel
+#[derive(Debug, Clone)]
+pub struct Histogram {
+    pub red: [u32; 256],
+    pub green: [u32; 256],
+    pub blue: [u32; 256],
+    pub luma: [u32; 256],
+}
+
+/// Intensities of an RGBA image, with the row of the top of the image first
+pub struct Intensities<'a> {
+    width: usize,
+    height: usize,
+    rgba: &'a [u8],
+}
+
+impl<'a> Intensities<'a> {
+    /// `None` when the buffer does not hold `width * height` RGBA pixels
+    pub fn new(width: usize, height: usize, rgba: &'a [u8]) -> Option<Self> {
+        (rgba.len() == 4 * width * height).then_some(Self { width, height, rgba })
+    }
+
+    pub fn size(&self) -> [usize; 2] {
+        [self.width, self.height]
+    }
+
+    pub fn rgba(&self, x: usize, y: usize) -> [u8; 4] {
+        let index = 4 * (y * self.width + x);
+        [self.rgba[index], self.rgba[index + 1], self.rgba[index + 2], self.rgba[index + 3]]
+    }
+
+    pub fn luma(&self, x: usize, y: usize) -> u8 {
+        let [r, g, b, _] = self.rgba(x, y);
+        luma([r, g, b])
+    }
+
+    /// Luma at a position in pixels, interpolated between the centers of the four nearest pixels
+    pub fn sample(&self, pixel: Point) -> Option<f64> {
+        if self.width == 0 || self.height == 0 {
+            return None;
+        }
+        let (u, v) = (pixel.x - 0.5, pixel.y - 0.5);
+        let (max_x, max_y) = ((self.width - 1) as f64, (self.height - 1) as f64);
+        if !(-0.5..=max_x + 0.5).contains(&u) || !(-0.5..=max_y + 0.5).contains(&v) {
+            return None;
+        }
+        // Pixels on the border extend to the edge of the image
+        let (u, v) = (u.clamp(0., max_x), v.clamp(0., max_y));
+        let (x0, y0) = (u.floor() as usize, v.floor() as usize);
+        let (x1, y1) = ((x0 + 1).min(self.width - 1), (y0 + 1).min(self.height - 1));
+        let (tx, ty) = (u - x0 as f64, v - y0 as f64);
+        let value = |x, y| self.luma(x, y) as f64;
+        let top = value(x0, y0) * (1. - tx) + value(x1, y0) * tx;
+        let bottom = value(x0, y1) * (1. - tx) + value(x1, y1) * tx;
+        Some(top * (1. - ty) + bottom * ty)
+    }
+
+    pub fn histogram(&self, region: Region) -> Histogram {
+        let mut histogram = Histogram { red: [0; 256], green: [0; 256], blue: [0; 256], luma: [0; 256] };
+        for y in 0..self.height {
+            for x in (0..self.width).filter(|&x| region.contains(x, y)) {
+                let [r, g, b, _] = self.rgba(x, y);
+                histogram.red[r as usize] += 1;
+                histogram.green[g as usize] += 1;
+                histogram.blue[b as usize] += 1;
+                histogram.luma[luma([r, g, b]) as usize] += 1;
+            }
+        }
+        histogram
+    }
+
+    /// Luma sampled every pixel along a segment in pixel coordinates, with the distance from its start
+    pub fn profile(&self, [start, end]: [Point; 2]) -> Vec<(f64, f64)> {
+        let length = (end.x - start.x).hypot(end.y - start.y);
+        let samples = length.ceil().max(1.) as usize;
+        (0..=samples)
+            .filter_map(|index| {
+                let t = index as f64 / samples as f64;
+                let point = Point::new(start.x + t * (end.x - start.x), start.y + t * (end.y - start.y));
+                self.sample(point).map(|value| (t * length, value))
+            })
+            .collect()
+    }
+}
+
+/// Distances where a profile crosses the middle of its darkest and brightest values
+pub fn edge_crossings(profile: &[(f64, f64)]) -> Vec<f64> {
+    let (min, max) = profile.iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &(_, value)| (min.min(value), max.max(value)));
+    if max <= min {
+        return Vec::new();
+    }
+    let level = 0.5 * (min + max);
+    profile.windows(2)
+        .filter_map(|pair| {
+            let [(d0, v0), (d1, v1)] = [pair[0], pair[1]];
+            ((v0 - level) * (v1 - level) < 0. || (v1 == level && v0 != level))
+                .then(|| d0 + (level - v0) / (v1 - v0) * (d1 - d0))
+        })
+        .collect()
+}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+use crate::color::Color;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Layer {
+    pub name: String,
+    pub color: Color,
+    pub width: f32,
+    pub visible: bool,
+    pub locked: bool,
+    /// Nominal shapes imported from a drawing
+    #[serde(default)]
+    pub reference: bool,
+}
+
+impl Layer {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            color: Color::BLACK,
+            width: 3.0,
+            visible: true,
+            locked: false,
+            reference: false,
+        }
+    }
+}
+
+impl Default for Layer {
+    fn default() -> Self {
+        Self::new("Default")
+    }
+}
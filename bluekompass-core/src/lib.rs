@@ -0,0 +1,23 @@
+//! Geometry core of BlueKompass, free of any GUI dependency
+
+pub mod point;
+pub mod maths;
+pub mod coordinates;
+pub mod calibration;
+pub mod adjustments;
+pub mod intensity;
+pub mod orientation;
+pub mod color;
+pub mod layers;
+pub mod style;
+pub mod shapes;
+pub mod tolerance;
+pub mod measure;
+pub mod deviation;
+pub mod project;
+pub mod export;
+pub mod import;
+pub mod transform;
+pub mod homography;
+pub mod checkerboard;
+pub mod distortion;
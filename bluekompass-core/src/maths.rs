@@ -0,0 +1,63 @@
+use crate::point::{Point, Vec2};
+
+/// Relative tolerance on the sine of the angle between two segments to consider them collinear
+const COLLINEAR_TOLERANCE: f64 = 1e-9;
+
+fn compute_intersection_line_to_line(p1: &Vec2, d1: &Vec2, p2: &Vec2, d2: &Vec2) -> Option<Vec2> {
+    let p1p2 = *p2 - *p1;
+    let (a, b) = (*d1).into();
+    let (c, d) = (*d2).into();
+    let det = a * d - b * c;
+    if det == 0. { return None; }
+    let k = Vec2::new(d / det, -c / det).dot(p1p2);
+    Some(k * *d1 + *p1)
+}
+
+/// Center of the circle passing through three points, `None` when the points are collinear or coincident
+pub fn compute_circle_center(points: &[Point; 3]) -> Option<Point> {
+    let [a, b, c] = points.map(|p| p.to_vec2());
+    let ab = b - a;
+    let bc = c - b;
+    // Also degenerate for NaN and for segments of zero length
+    let cross = ab.x * bc.y - ab.y * bc.x;
+    let threshold = COLLINEAR_TOLERANCE * ab.length() * bc.length();
+    if cross.is_nan() || cross.abs() <= threshold {
+        return None;
+    }
+    let middle_ab = 0.5 * (a + b);
+    let middle_bc = 0.5 * (b + c);
+    compute_intersection_line_to_line(&middle_ab, &ab.rot90(), &middle_bc, &bc.rot90())
+        .map(|center| Point::new(center.x, center.y))
+        .filter(|center| center.x.is_finite() && center.y.is_finite())
+}
+
+pub fn compute_circle_radius(center: &Vec2, circle_point: &Vec2) -> f64 {
+    (*center - *circle_point).length()
+}
+
+/// Solve the square system `a x = b` by Gaussian elimination with partial pivoting, `None` when it is singular
+pub fn solve(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let n = b.len();
+    for column in 0..n {
+        let pivot = (column..n).max_by(|&i, &j| a[i][column].abs().total_cmp(&a[j][column].abs()))?;
+        if a[pivot][column].abs() < 1e-12 {
+            return None;
+        }
+        a.swap(column, pivot);
+        b.swap(column, pivot);
+        let pivot_row = a[column].clone();
+        for row in column + 1..n {
+            let factor = a[row][column] / pivot_row[column];
+            for (value, pivot_value) in a[row][column..].iter_mut().zip(&pivot_row[column..]) {
+                *value -= factor * pivot_value;
+            }
+            b[row] -= factor * b[column];
+        }
+    }
+    let mut x = vec![0.; n];
+    for row in (0..n).rev() {
+        let sum: f64 = (row + 1..n).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+    Some(x)
+}
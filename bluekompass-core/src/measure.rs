@@ -0,0 +1,79 @@
+use crate::calibration::Calibration;
+use crate::shapes::{Shape, ShapeKind};
+use crate::tolerance::Tolerance;
+
+/// Value measured on a shape, in calibrated units, with its tolerance if any
+#[derive(Debug, Clone)]
+pub struct Measurement {
+    pub shape_id: usize,
+    pub shape_name: String,
+    pub kind: ShapeKind,
+    pub name: &'static str,
+    pub value: f64,
+    pub unit: String,
+    pub tolerance: Option<Tolerance>,
+}
+
+impl Measurement {
+    /// Whether the value is within its tolerance, `None` without tolerance
+    pub fn passed(&self) -> Option<bool> {
+        self.tolerance.map(|tolerance| tolerance.check(self.value))
+    }
+}
+
+/// Measurements of a shape
+pub fn measure_shape(shape: &dyn Shape, calibration: &Calibration) -> Vec<Measurement> {
+    let properties = shape.properties();
+    shape.measurements()
+        .into_iter()
+        .map(|(name, value)| {
+            let (value, unit) = calibrated(name, value, calibration);
+            Measurement {
+                shape_id: properties.id,
+                shape_name: properties.name.clone(),
+                kind: shape.kind(),
+                name,
+                value,
+                unit: unit.to_string(),
+                tolerance: properties.tolerances.get(name).copied(),
+            }
+        })
+        .collect()
+}
+
+/// Measurements of every shape
+pub fn measure(shapes: &[Box<dyn Shape>], calibration: &Calibration) -> Vec<Measurement> {
+    shapes.iter()
+        .flat_map(|shape| measure_shape(shape.as_ref(), calibration))
+        .collect()
+}
+
+/// Whether every toleranced measurement of a shape is within its tolerance, `None` without tolerance
+pub fn shape_passed(shape: &dyn Shape, calibration: &Calibration) -> Option<bool> {
+    measure_shape(shape, calibration)
+        .iter()
+        .filter_map(Measurement::passed)
+        .reduce(|passed, other| passed && other)
+}
+
+/// Value of a measurement in calibrated units with its unit, angles stay in degrees
+pub fn calibrated<'a>(name: &str, value: f64, calibration: &'a Calibration) -> (f64, &'a str) {
+    match name {
+        "Angle" => (calibration.angle(value), "°"),
+        _ => (calibration.length(value), &calibration.unit),
+    }
+}
+
+/// Name of a shape followed by its measurements, as displayed next to it
+pub fn label(shape: &dyn Shape, calibration: &Calibration) -> String {
+    let measurements = shape.measurements()
+        .into_iter()
+        .map(|(name, value)| {
+            let (value, unit) = calibrated(name, value, calibration);
+            let separator = if unit == "°" { "" } else { " " };
+            format!("{name} {value:.2}{separator}{unit}")
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("{}: {measurements}", shape.properties().name)
+}
@@ -0,0 +1,236 @@
+use serde::{Deserialize, Serialize};
+
+use crate::coordinates::{pixel_to_plot, plot_to_pixel};
+use crate::distortion::Distortion;
+use crate::homography::Homography;
+use crate::point::Point;
+
+/// Rotation, flip, rectification or lens correction of an image, applied to a copy of its pixels and to the shapes drawn on it
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ImageOperation {
+    RotateClockwise,
+    RotateCounterclockwise,
+    FlipHorizontal,
+    FlipVertical,
+    /// Counterclockwise rotation in degrees around the center, the image is enlarged to keep its corners
+    Rotate(f64),
+    /// Perspective rectification, from pixels of the image to pixels of a new image of the given size
+    Rectify { homography: Homography, size: [usize; 2] },
+    /// Removal of the lens distortion, the size is kept
+    Undistort(Distortion),
+}
+
+/// Largest side of a rectified image
+const MAX_RECTIFIED_SIDE: f64 = 16384.;
+
+/// Rectification of a rectangle of the image seen from the front
+#[derive(Debug, Clone, Copy)]
+pub struct Rectification {
+    pub operation: ImageOperation,
+    /// Pixels per unit of length of the rectified image
+    pub scale: f64,
+    /// Root mean square distance in pixels of the rectified image between the points and their known positions,
+    /// zero with the four corners only since they are fitted exactly
+    pub residual: f64,
+}
+
+/// Rectification making a rectangle of the image, given by its corners in pixels in the order top left, top right,
+/// bottom right, bottom left, look like it is seen from the front
+///
+/// `checks` are other points of the plane of the rectangle, in pixels, with their position in units from its top left
+/// corner along its width and its height. With them the homography is fitted by least squares to all the points and
+/// the residual tells how well they agree.
+pub fn rectification(
+    corners: [Point; 4],
+    [width, height]: [f64; 2],
+    checks: &[(Point, Point)],
+    size: [usize; 2],
+) -> Option<Rectification> {
+    if !(width > 0. && height > 0.) {
+        return None;
+    }
+    let distance = |a: Point, b: Point| (b.x - a.x).hypot(b.y - a.y);
+    let [a, b, c, d] = corners;
+    // Keep about the resolution of the rectangle in the image
+    let scale = 0.5 * ((distance(a, b) + distance(d, c)) / (2. * width) + (distance(a, d) + distance(b, c)) / (2. * height));
+    let positions = [(0., 0.), (width, 0.), (width, height), (0., height)].map(|(x, y)| Point::new(x, y));
+    let points: Vec<Point> = corners.into_iter().chain(checks.iter().map(|(point, _)| *point)).collect();
+    let targets: Vec<Point> = positions.into_iter()
+        .chain(checks.iter().map(|(_, position)| *position))
+        .map(|position| Point::new(position.x * scale, position.y * scale))
+        .collect();
+    let homography = Homography::fit(&points, &targets)?;
+    let residual = homography.residual(&points, &targets)?;
+
+    // The whole image when it stays in front of the camera, bounded to a margin around the rectangle
+    let [image_width, image_height] = size.map(|length| length as f64);
+    let margin = image_width.max(image_height);
+    let limit = [-margin, -margin, width * scale + margin, height * scale + margin];
+    let image_corners = [(0., 0.), (image_width, 0.), (image_width, image_height), (0., image_height)]
+        .map(|(x, y)| Point::new(x, y));
+    let [h6, h7, h8] = [homography.0[6], homography.0[7], homography.0[8]];
+    let in_front = image_corners.iter().all(|point| h6 * point.x + h7 * point.y + h8 > 0.);
+    let bounds = match image_corners.map(|point| homography.apply(point)) {
+        [Some(p), Some(q), Some(r), Some(s)] if in_front => {
+            let xs = [p.x, q.x, r.x, s.x];
+            let ys = [p.y, q.y, r.y, s.y];
+            let min = |values: [f64; 4]| values.into_iter().fold(f64::INFINITY, f64::min);
+            let max = |values: [f64; 4]| values.into_iter().fold(f64::NEG_INFINITY, f64::max);
+            [min(xs).max(limit[0]), min(ys).max(limit[1]), max(xs).min(limit[2]), max(ys).min(limit[3])]
+        }
+        _ => limit,
+    };
+    let new_size = [bounds[2] - bounds[0], bounds[3] - bounds[1]];
+    if new_size.iter().any(|&length| !(1.0..=MAX_RECTIFIED_SIDE).contains(&length)) {
+        return None;
+    }
+
+    // Shift the bounds onto the origin of the new image
+    let mut matrix = homography.0;
+    for column in 0..3 {
+        matrix[column] -= bounds[0] * matrix[6 + column];
+        matrix[3 + column] -= bounds[1] * matrix[6 + column];
+    }
+    let operation = ImageOperation::Rectify { homography: Homography(matrix), size: new_size.map(|length| length.ceil() as usize) };
+    Some(Rectification { operation, scale, residual })
+}
+
+impl ImageOperation {
+    fn sin_cos(&self) -> (f64, f64) {
+        match *self {
+            ImageOperation::RotateClockwise => (-1., 0.),
+            ImageOperation::RotateCounterclockwise => (1., 0.),
+            ImageOperation::Rotate(degrees) => degrees.to_radians().sin_cos(),
+            ImageOperation::FlipHorizontal
+            | ImageOperation::FlipVertical
+            | ImageOperation::Rectify { .. }
+            | ImageOperation::Undistort(_) => (0., 1.),
+        }
+    }
+
+    /// Size of the image once transformed
+    pub fn size(&self, [width, height]: [usize; 2]) -> [usize; 2] {
+        match *self {
+            ImageOperation::RotateClockwise | ImageOperation::RotateCounterclockwise => [height, width],
+            ImageOperation::FlipHorizontal | ImageOperation::FlipVertical | ImageOperation::Undistort(_) => [width, height],
+            ImageOperation::Rectify { size, .. } => size,
+            ImageOperation::Rotate(_) => {
+                let (sin, cos) = self.sin_cos();
+                let (sin, cos) = (sin.abs(), cos.abs());
+                let (width, height) = (width as f64, height as f64);
+                // Rounded first so that quarter turns keep the size
+                let length = |length: f64| ((length * 1e6).round() / 1e6).ceil().max(1.) as usize;
+                [length(width * cos + height * sin), length(width * sin + height * cos)]
+            }
+        }
+    }
+
+    /// Position in the transformed image of a pixel position of an image of the given size
+    pub fn map_pixel(&self, pixel: Point, size: [usize; 2]) -> Point {
+        let [width, height] = size.map(|length| length as f64);
+        match self {
+            ImageOperation::FlipHorizontal => Point::new(width - pixel.x, pixel.y),
+            ImageOperation::FlipVertical => Point::new(pixel.x, height - pixel.y),
+            // Points sent to infinity are left in place
+            ImageOperation::Rectify { homography, .. } => homography.apply(pixel).unwrap_or(pixel),
+            ImageOperation::Undistort(distortion) => distortion.undistort(pixel),
+            _ => {
+                // Counterclockwise on screen, the pixel Y axis points down
+                let [new_width, new_height] = self.size(size).map(|length| length as f64);
+                let (sin, cos) = self.sin_cos();
+                let (x, y) = (pixel.x - 0.5 * width, pixel.y - 0.5 * height);
+                Point::new(0.5 * new_width + x * cos + y * sin, 0.5 * new_height - x * sin + y * cos)
+            }
+        }
+    }
+
+    /// Inverse of `map_pixel`, `size` being the size of the image before the operation
+    pub fn unmap_pixel(&self, pixel: Point, size: [usize; 2]) -> Point {
+        let [width, height] = size.map(|length| length as f64);
+        match self {
+            ImageOperation::FlipHorizontal | ImageOperation::FlipVertical => self.map_pixel(pixel, size),
+            ImageOperation::Rectify { homography, .. } => homography.inverse()
+                .and_then(|inverse| inverse.apply(pixel))
+                .unwrap_or(pixel),
+            ImageOperation::Undistort(distortion) => distortion.distort(pixel),
+            _ => {
+                let [new_width, new_height] = self.size(size).map(|length| length as f64);
+                let (sin, cos) = self.sin_cos();
+                let (x, y) = (pixel.x - 0.5 * new_width, pixel.y - 0.5 * new_height);
+                Point::new(0.5 * width + x * cos - y * sin, 0.5 * height + x * sin + y * cos)
+            }
+        }
+    }
+
+    /// Plot point following the image, `size` being the size of the image before the operation
+    pub fn map_point(&self, point: Point, size: [usize; 2]) -> Point {
+        pixel_to_plot(self.map_pixel(plot_to_pixel(point), size))
+    }
+
+    pub fn unmap_point(&self, point: Point, size: [usize; 2]) -> Point {
+        pixel_to_plot(self.unmap_pixel(plot_to_pixel(point), size))
+    }
+
+    /// Transform RGBA pixels, returns the new size and pixels
+    ///
+    /// Quarter turns and flips move pixels as they are, other operations interpolate them and leave
+    /// transparent what is outside of the image.
+    pub fn apply(&self, size: [usize; 2], rgba: &[u8]) -> ([usize; 2], Vec<u8>) {
+        let [width, height] = size;
+        let new_size = self.size(size);
+        let exact = matches!(
+            self,
+            ImageOperation::RotateClockwise
+                | ImageOperation::RotateCounterclockwise
+                | ImageOperation::FlipHorizontal
+                | ImageOperation::FlipVertical
+        );
+        // Inverted once rather than for every pixel
+        let inverse = match self {
+            ImageOperation::Rectify { homography, .. } => Some(homography.inverse()),
+            _ => None,
+        };
+        let unmap = |source: Point| match inverse {
+            Some(inverse) => inverse.and_then(|inverse| inverse.apply(source)).unwrap_or(source),
+            None => self.unmap_pixel(source, size),
+        };
+        let pixel = |x: isize, y: isize| -> [f64; 4] {
+            if x < 0 || y < 0 || x as usize >= width || y as usize >= height {
+                return [0.; 4];
+            }
+            let index = 4 * (y as usize * width + x as usize);
+            [0, 1, 2, 3].map(|channel| rgba[index + channel] as f64)
+        };
+
+        let mut output = Vec::with_capacity(4 * new_size[0] * new_size[1]);
+        for y in 0..new_size[1] {
+            for x in 0..new_size[0] {
+                let source = unmap(Point::new(x as f64 + 0.5, y as f64 + 0.5));
+                let value = if exact {
+                    pixel(source.x.floor() as isize, source.y.floor() as isize)
+                } else {
+                    // Bilinear between the centers of the four nearest pixels
+                    let (u, v) = (source.x - 0.5, source.y - 0.5);
+                    let (x0, y0) = (u.floor(), v.floor());
+                    let (tx, ty) = (u - x0, v - y0);
+                    let (x0, y0) = (x0 as isize, y0 as isize);
+                    let [a, b, c, d] = [pixel(x0, y0), pixel(x0 + 1, y0), pixel(x0, y0 + 1), pixel(x0 + 1, y0 + 1)];
+                    [0, 1, 2, 3].map(|channel| {
+                        let top = a[channel] * (1. - tx) + b[channel] * tx;
+                        let bottom = c[channel] * (1. - tx) + d[channel] * tx;
+                        top * (1. - ty) + bottom * ty
+                    })
+                };
+                output.extend(value.map(|channel| channel.round() as u8));
+            }
+        }
+        (new_size, output)
+    }
+}
+
+/// Counterclockwise rotation in degrees, between -90 and 90, that makes a direction horizontal or vertical
+pub fn straightening_rotation(direction_degrees: f64, vertical: bool) -> f64 {
+    let target = if vertical { 90. } else { 0. };
+    let rotation = (target - direction_degrees).rem_euclid(180.);
+    if rotation > 90. { rotation - 180. } else { rotation }
+}
@@ -0,0 +1,167 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::adjustments::Adjustments;
+use crate::calibration::{Calibration, Resolution};
+use crate::deviation::{default_pairing_distance, DeviationTolerance, DEFAULT_PAIRING_DISTANCE};
+use crate::layers::Layer;
+use crate::orientation::ImageOperation;
+use crate::point::Point;
+use crate::shapes::{build_shape, Properties, Shape, ShapeKind};
+use crate::style::Style;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShapeRecord {
+    pub kind: ShapeKind,
+    pub points: Vec<[f64; 2]>,
+    pub properties: Properties,
+}
+
+/// Version of the project files written
+///
+/// Version 0 projects have the image centered on the plot origin, from version 1 its top left corner is on it.
+pub const VERSION: u32 = 1;
+
+/// Content of a BlueKompass project file (`.bkp`)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Project {
+    #[serde(default)]
+    pub version: u32,
+    pub image: Option<PathBuf>,
+    pub layers: Vec<Layer>,
+    #[serde(default)]
+    pub default_style: Style,
+    #[serde(default)]
+    pub calibration: Calibration,
+    #[serde(default)]
+    pub deviation_tolerance: DeviationTolerance,
+    /// Largest distance between a traced shape and its nominal shape to compare them, in calibrated units
+    #[serde(default = "default_pairing_distance")]
+    pub pairing_distance: f64,
+    #[serde(default)]
+    pub adjustments: Adjustments,
+    /// Rotations and flips of the image, the shapes are stored once they are applied
+    #[serde(default)]
+    pub image_operations: Vec<ImageOperation>,
+    pub shapes: Vec<ShapeRecord>,
+}
+
+impl Project {
+    /// Project of the shapes, settings are left to their default
+    pub fn new(image: Option<PathBuf>, layers: &[Layer], default_style: &Style, shapes: &[Box<dyn Shape>]) -> Self {
+        let shapes = shapes.iter()
+            .map(|shape| ShapeRecord {
+                kind: shape.kind(),
+                points: shape.as_slice().iter().map(|&point| point.into()).collect(),
+                properties: shape.properties().clone(),
+            })
+            .collect();
+        Self {
+            version: VERSION,
+            image,
+            layers: layers.to_vec(),
+            default_style: default_style.clone(),
+            calibration: Calibration::default(),
+            deviation_tolerance: DeviationTolerance::default(),
+            pairing_distance: DEFAULT_PAIRING_DISTANCE,
+            adjustments: Adjustments::default(),
+            image_operations: Vec::new(),
+            shapes,
+        }
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let project: Self = serde_json::from_str(&fs::read_to_string(path)?)?;
+        if project.layers.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "project has no layer"));
+        }
+        Ok(project)
+    }
+
+    /// Move the shapes of an older project to the current plot coordinates, given the size of its image
+    ///
+    /// Without the size the shapes are left in place and the project keeps its version, so that they are moved
+    /// once its image is found again. Returns whether the shapes are in the current plot coordinates.
+    pub fn upgrade(&mut self, image_size: Option<[usize; 2]>) -> bool {
+        if self.version == 0 {
+            let Some([width, height]) = image_size else {
+                return false;
+            };
+            let [dx, dy] = [0.5 * width as f64, -0.5 * height as f64];
+            for record in &mut self.shapes {
+                for [x, y] in &mut record.points {
+                    *x += dx;
+                    *y += dy;
+                }
+            }
+        }
+        self.version = VERSION;
+        true
+    }
+
+    /// Shapes and calibration of the project on an image of the given size and resolution
+    ///
+    /// The shapes follow the operations of the image from the pixels of the image of the project, whose size is
+    /// `project_size`, to the pixels of this one. An uncalibrated project takes the resolution of the image.
+    pub fn on_image(
+        &self,
+        project_size: Option<[usize; 2]>,
+        size: [usize; 2],
+        resolution: Option<Resolution>,
+    ) -> (Vec<Box<dyn Shape>>, Calibration) {
+        let mut shapes = self.shapes();
+        if let Some(project_size) = project_size.filter(|&project_size| project_size != size) {
+            let sizes = |mut size: [usize; 2]| -> Vec<[usize; 2]> {
+                self.image_operations.iter()
+                    .map(|operation| {
+                        let before = size;
+                        size = operation.size(size);
+                        before
+                    })
+                    .collect()
+            };
+            let (project_sizes, sizes) = (sizes(project_size), sizes(size));
+            let map = |point: Point| {
+                let original = self.image_operations.iter()
+                    .zip(&project_sizes)
+                    .rev()
+                    .fold(point, |point, (operation, &size)| operation.unmap_point(point, size));
+                self.image_operations.iter()
+                    .zip(&sizes)
+                    .fold(original, |point, (operation, &size)| operation.map_point(point, size))
+            };
+            for shape in &mut shapes {
+                shape.map_points(&map);
+            }
+        }
+
+        let mut calibration = self.calibration.clone();
+        if !calibration.is_calibrated() {
+            if let Some(reference) = resolution.and_then(|resolution| resolution.calibration()) {
+                calibration.scale = reference.scale;
+                calibration.unit = reference.unit;
+            }
+        }
+        (shapes, calibration)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    /// Rebuild the shapes of the project, skipping records with missing or degenerate points
+    pub fn shapes(&self) -> Vec<Box<dyn Shape>> {
+        self.shapes.iter()
+            .filter_map(|record| {
+                let points: Vec<Point> = record.points.iter().map(|&point| point.into()).collect();
+                let mut shape = build_shape(record.kind, &points).filter(|shape| shape.is_valid())?;
+                *shape.properties_mut() = record.properties.clone();
+                shape.properties_mut().layer = record.properties.layer.min(self.layers.len() - 1);
+                Some(shape)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,299 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::deviation::DeviationTolerance;
+use crate::maths::{compute_circle_center, compute_circle_radius};
+use crate::point::{Point, Vec2};
+use crate::style::Style;
+use crate::tolerance::Tolerance;
+
+pub trait Select {
+    fn select_from_point(&self, point: Vec2) -> f64;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ShapeKind {
+    Line,
+    Circle,
+}
+
+impl ShapeKind {
+    pub const ALL: [ShapeKind; 2] = [ShapeKind::Line, ShapeKind::Circle];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ShapeKind::Line => "Line",
+            ShapeKind::Circle => "Circle",
+        }
+    }
+}
+
+/// Geometric description of a shape, independent of its defining points
+#[derive(Debug, Clone, Copy)]
+pub enum Geometry {
+    Line([Point; 2]),
+    Circle { center: Point, radius: f64 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Properties {
+    pub id: usize,
+    pub name: String,
+    pub layer: usize,
+    pub visible: bool,
+    pub locked: bool,
+    #[serde(default)]
+    pub style: Style,
+    /// Tolerances of measurements, by measurement name
+    #[serde(default)]
+    pub tolerances: BTreeMap<String, Tolerance>,
+    /// Tolerance of the deviations of traced shapes when this one is nominal
+    #[serde(default)]
+    pub deviation_tolerance: Option<DeviationTolerance>,
+}
+
+impl Default for Properties {
+    fn default() -> Self {
+        Self {
+            id: 0,
+            name: String::new(),
+            layer: 0,
+            visible: true,
+            locked: false,
+            style: Style::default(),
+            tolerances: BTreeMap::new(),
+            deviation_tolerance: None,
+        }
+    }
+}
+
+pub trait Shape: Select {
+    fn kind(&self) -> ShapeKind;
+    fn properties(&self) -> &Properties;
+    fn properties_mut(&mut self) -> &mut Properties;
+    fn center(&self) -> Point;
+    /// Geometry of the shape, `None` when its defining points are degenerate
+    fn geometry(&self) -> Option<Geometry>;
+    fn measurements(&self) -> Vec<(&'static str, f64)>;
+    fn select(&mut self);
+    fn unselect(&mut self);
+    fn is_selected(&self) -> bool;
+    fn as_slice(&self) -> &[Point];
+    fn replace(&mut self, index: usize, point: Point);
+
+    fn is_valid(&self) -> bool {
+        self.geometry().is_some()
+    }
+
+    /// Replace a point only if the shape stays valid, returns whether the point was replaced
+    fn try_replace(&mut self, index: usize, point: Point) -> bool {
+        let previous = self.as_slice()[index];
+        self.replace(index, point);
+        if !self.is_valid() {
+            self.replace(index, previous);
+            return false;
+        }
+        true
+    }
+
+    /// Move every defining point through `map`
+    fn map_points(&mut self, map: &dyn Fn(Point) -> Point) {
+        for index in 0..self.as_slice().len() {
+            let point = self.as_slice()[index];
+            self.replace(index, map(point));
+        }
+    }
+
+    fn translate(&mut self, delta: Vec2) {
+        self.map_points(&|point| Point::new(point.x + delta.x, point.y + delta.y));
+    }
+}
+
+/// Build a shape of the given kind from its defining points, the shape may be invalid
+pub fn build_shape(kind: ShapeKind, points: &[Point]) -> Option<Box<dyn Shape>> {
+    match kind {
+        ShapeKind::Line if points.len() >= 2 => Some(Box::new(Line::new([points[0], points[1]]))),
+        ShapeKind::Circle if points.len() >= 3 => Some(Box::new(Circle::new([points[0], points[1], points[2]]))),
+        _ => None,
+    }
+}
+
+#[derive(Debug)]
+pub struct Line {
+    points: [Point; 2],
+    properties: Properties,
+    selected: bool,
+}
+
+impl Line {
+    pub fn new(points: [Point; 2]) -> Self {
+        Self { points, properties: Properties::default(), selected: false }
+    }
+
+    pub fn length(&self) -> f64 {
+        let [a, b] = self.points;
+        (b.x - a.x).hypot(b.y - a.y)
+    }
+
+    /// Angle of the line in degrees, measured from the X axis
+    pub fn angle(&self) -> f64 {
+        let [a, b] = self.points;
+        (b.y - a.y).atan2(b.x - a.x).to_degrees()
+    }
+}
+
+impl Shape for Line {
+    fn kind(&self) -> ShapeKind {
+        ShapeKind::Line
+    }
+
+    fn properties(&self) -> &Properties {
+        &self.properties
+    }
+
+    fn properties_mut(&mut self) -> &mut Properties {
+        &mut self.properties
+    }
+
+    fn center(&self) -> Point {
+        let [a, b] = self.points;
+        Point::new(0.5 * (a.x + b.x), 0.5 * (a.y + b.y))
+    }
+
+    fn geometry(&self) -> Option<Geometry> {
+        let [a, b] = self.points;
+        let finite = [a.x, a.y, b.x, b.y].iter().all(|coordinate| coordinate.is_finite());
+        (finite && a != b).then_some(Geometry::Line(self.points))
+    }
+
+    fn measurements(&self) -> Vec<(&'static str, f64)> {
+        if !self.is_valid() {
+            return Vec::new();
+        }
+        vec![("Length", self.length()), ("Angle", self.angle())]
+    }
+
+    fn select(&mut self) {
+        self.selected = true;
+    }
+
+    fn unselect(&mut self) {
+        self.selected = false;
+    }
+
+    fn is_selected(&self) -> bool {
+        self.selected
+    }
+
+    fn as_slice(&self) -> &[Point] {
+        self.points.as_slice()
+    }
+
+    fn replace(&mut self, index: usize, point: Point) {
+        self.points[index] = point;
+    }
+}
+
+impl Select for Line {
+    fn select_from_point(&self, point: Vec2) -> f64 {
+        let [a, b] = self.points.map(|p| p.to_vec2());
+        let ab = b - a;
+        let ap = point - a;
+        let k = ap.dot(ab) / ab.length_sq();
+        if (0. ..=1.).contains(&k) { // point is between A and B
+            // Distance between a point and the line
+            return (ap.length_sq() - k * k * ab.length_sq()).sqrt();
+        }
+        f64::INFINITY
+    }
+}
+
+#[derive(Debug)]
+pub struct Circle {
+    points: [Point; 3],
+    center: Option<Point>,
+    radius: f64,
+    properties: Properties,
+    selected: bool,
+}
+
+impl Circle {
+    pub fn new(points: [Point; 3]) -> Self {
+        let mut circle = Self { points, center: None, radius: 0., properties: Properties::default(), selected: false };
+        circle.update();
+        circle
+    }
+
+    fn update(&mut self) {
+        self.center = compute_circle_center(&self.points);
+        self.radius = self.center
+            .map_or(0., |center| compute_circle_radius(&center.to_vec2(), &self.points[0].to_vec2()));
+    }
+}
+
+impl Shape for Circle {
+    fn kind(&self) -> ShapeKind {
+        ShapeKind::Circle
+    }
+
+    fn properties(&self) -> &Properties {
+        &self.properties
+    }
+
+    fn properties_mut(&mut self) -> &mut Properties {
+        &mut self.properties
+    }
+
+    fn center(&self) -> Point {
+        // Fall back on the centroid of the points for a degenerate circle
+        self.center.unwrap_or_else(|| {
+            let [a, b, c] = self.points;
+            Point::new((a.x + b.x + c.x) / 3., (a.y + b.y + c.y) / 3.)
+        })
+    }
+
+    fn geometry(&self) -> Option<Geometry> {
+        self.center.map(|center| Geometry::Circle { center, radius: self.radius })
+    }
+
+    fn measurements(&self) -> Vec<(&'static str, f64)> {
+        if !self.is_valid() {
+            return Vec::new();
+        }
+        vec![("Radius", self.radius), ("Diameter", 2. * self.radius)]
+    }
+
+    fn select(&mut self) {
+        self.selected = true;
+    }
+
+    fn unselect(&mut self) {
+        self.selected = false;
+    }
+
+    fn is_selected(&self) -> bool {
+        self.selected
+    }
+
+    fn as_slice(&self) -> &[Point] {
+        self.points.as_slice()
+    }
+
+    fn replace(&mut self, index: usize, point: Point) {
+        self.points[index] = point;
+        self.update();
+    }
+}
+
+impl Select for Circle {
+    fn select_from_point(&self, point: Vec2) -> f64 {
+        let Some(center) = self.center else {
+            return f64::INFINITY;
+        };
+        let radius2 = self.radius * self.radius;
+        // circle equation ^ 2 = radius ^ 2
+        // vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv
+        (((point - center.to_vec2()).length_sq() - radius2) / self.radius).abs()
+    }
+}
@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+
+use crate::color::Color;
+use crate::layers::Layer;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Dash {
+    Solid,
+    Dashed,
+    Dotted,
+}
+
+impl Dash {
+    pub const ALL: [Dash; 3] = [Dash::Solid, Dash::Dashed, Dash::Dotted];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Dash::Solid => "Solid",
+            Dash::Dashed => "Dashed",
+            Dash::Dotted => "Dotted",
+        }
+    }
+}
+
+/// Appearance of a shape, stroke color and width fall back on the layer ones when unset
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Style {
+    pub color: Option<Color>,
+    pub width: Option<f32>,
+    pub dash: Dash,
+    pub show_grips: bool,
+    pub marker_size: f32,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            color: None,
+            width: None,
+            dash: Dash::Solid,
+            show_grips: true,
+            marker_size: 6.0,
+        }
+    }
+}
+
+impl Style {
+    pub fn color(&self, layer: &Layer) -> Color {
+        self.color.unwrap_or(layer.color)
+    }
+
+    pub fn width(&self, layer: &Layer) -> f32 {
+        self.width.unwrap_or(layer.width)
+    }
+
+    /// Length of dashes and gaps, scaled with the width so that thick lines stay readable
+    pub fn dash_pattern(&self, layer: &Layer) -> Option<[f32; 2]> {
+        let width = self.width(layer);
+        match self.dash {
+            Dash::Solid => None,
+            Dash::Dashed => Some([4. * width, 4. * width]),
+            Dash::Dotted => Some([width, 2. * width]),
+        }
+    }
+}
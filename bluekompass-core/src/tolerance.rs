@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+
+/// Nominal value of a measurement with the allowed deviations below and above it
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Tolerance {
+    pub nominal: f64,
+    pub lower: f64,
+    pub upper: f64,
+}
+
+impl Tolerance {
+    /// Symmetric tolerance around the nominal value
+    pub fn new(nominal: f64, deviation: f64) -> Self {
+        Self { nominal, lower: -deviation.abs(), upper: deviation.abs() }
+    }
+
+    pub fn min(&self) -> f64 {
+        self.nominal + self.lower
+    }
+
+    pub fn max(&self) -> f64 {
+        self.nominal + self.upper
+    }
+
+    pub fn check(&self, value: f64) -> bool {
+        (self.min()..=self.max()).contains(&value)
+    }
+}
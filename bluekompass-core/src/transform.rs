@@ -0,0 +1,41 @@
+use crate::point::{Point, Vec2};
+
+/// Uniform scale and rotation around the origin followed by a translation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Similarity {
+    pub scale: f64,
+    /// Counterclockwise rotation in radians
+    pub rotation: f64,
+    pub translation: Vec2,
+}
+
+impl Default for Similarity {
+    fn default() -> Self {
+        Self { scale: 1.0, rotation: 0.0, translation: Vec2::default() }
+    }
+}
+
+impl Similarity {
+    /// Transform moving the `from` points onto the `to` points, `None` when either pair is coincident
+    pub fn from_two_points(from: [Point; 2], to: [Point; 2]) -> Option<Self> {
+        let [a, b] = from.map(Point::to_vec2);
+        let [c, d] = to.map(Point::to_vec2);
+        let (source, target) = (b - a, d - c);
+        if source.length_sq() == 0. || target.length_sq() == 0. {
+            return None;
+        }
+        let scale = target.length() / source.length();
+        let rotation = target.y.atan2(target.x) - source.y.atan2(source.x);
+        let mut similarity = Self { scale, rotation, translation: Vec2::default() };
+        similarity.translation = c - similarity.apply(from[0]).to_vec2();
+        Some(similarity)
+    }
+
+    pub fn apply(&self, point: Point) -> Point {
+        let (sin, cos) = self.rotation.sin_cos();
+        Point::new(
+            self.scale * (cos * point.x - sin * point.y) + self.translation.x,
+            self.scale * (sin * point.x + cos * point.y) + self.translation.y,
+        )
+    }
+}
@@ -0,0 +1,28 @@
+//! Display adjustments must change the color of pixels only
+
+use bluekompass_core::adjustments::Adjustments;
+
+#[test]
+fn default_adjustments_keep_pixels() {
+    let mut rgba = vec![12, 34, 56, 78, 200, 100, 0, 255];
+    Adjustments::default().apply(&mut rgba);
+    assert_eq!(rgba, [12, 34, 56, 78, 200, 100, 0, 255]);
+}
+
+#[test]
+fn invert_and_grayscale_keep_alpha() {
+    let mut rgba = vec![255, 255, 255, 128, 0, 0, 0, 255];
+    Adjustments { invert: true, ..Adjustments::default() }.apply(&mut rgba);
+    assert_eq!(rgba, [0, 0, 0, 128, 255, 255, 255, 255]);
+
+    let mut rgba = vec![255, 0, 0, 255];
+    Adjustments { grayscale: true, ..Adjustments::default() }.apply(&mut rgba);
+    assert_eq!(rgba, [54, 54, 54, 255]);
+}
+
+#[test]
+fn auto_levels_stretch_intensities_to_the_whole_range() {
+    let mut rgba: Vec<u8> = [100u8, 150, 200].iter().flat_map(|&value| [value, value, value, 255]).collect();
+    Adjustments { auto_levels: true, ..Adjustments::default() }.apply(&mut rgba);
+    assert_eq!([rgba[0], rgba[4], rgba[8]], [0, 128, 255]);
+}
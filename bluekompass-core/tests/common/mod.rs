@@ -0,0 +1,15 @@
+//! Helpers shared by the integration tests, each test crate uses only some of them
+
+#![allow(dead_code)]
+
+use bluekompass_core::point::Point;
+
+/// Whether both coordinates of the points differ by less than the tolerance
+pub fn close_within(a: Point, b: Point, tolerance: f64) -> bool {
+    (a.x - b.x).abs() < tolerance && (a.y - b.y).abs() < tolerance
+}
+
+/// Whether the points are the same up to rounding errors
+pub fn close(a: Point, b: Point) -> bool {
+    close_within(a, b, 1e-9)
+}
@@ -0,0 +1,52 @@
+//! Plot coordinates are image pixels, read out along the Y axis of the chosen convention
+
+use bluekompass_core::calibration::Calibration;
+use bluekompass_core::coordinates::{pixel_to_plot, plot_to_pixel, YAxis};
+use bluekompass_core::export::shape_rows;
+use bluekompass_core::layers::Layer;
+use bluekompass_core::measure::measure;
+use bluekompass_core::point::Point;
+use bluekompass_core::project::{Project, VERSION};
+use bluekompass_core::shapes::{Line, Shape};
+
+#[test]
+fn pixel_centers_are_at_half_coordinates() {
+    let center = pixel_to_plot(Point::new(2.5, 3.5));
+    assert_eq!(center, Point::new(2.5, -3.5));
+    assert_eq!(plot_to_pixel(center), Point::new(2.5, 3.5));
+}
+
+#[test]
+fn readouts_follow_the_y_axis() {
+    let shapes: Vec<Box<dyn Shape>> = vec![Box::new(Line::new([Point::new(0., 0.), Point::new(10., -10.)]))];
+    let up = Calibration::default();
+    let down = Calibration { y_axis: YAxis::Down, ..Calibration::default() };
+    assert!(!down.is_calibrated());
+
+    assert_eq!(shape_rows(&shapes, &[Layer::default()], &up)[0].points[1], [10., -10.]);
+    assert_eq!(shape_rows(&shapes, &[Layer::default()], &down)[0].points[1], [10., 10.]);
+    assert!((measure(&shapes, &up)[1].value + 45.).abs() < 1e-9);
+    assert!((measure(&shapes, &down)[1].value - 45.).abs() < 1e-9);
+}
+
+#[test]
+fn older_projects_are_moved_to_the_image_corner() {
+    let shapes: Vec<Box<dyn Shape>> = vec![Box::new(Line::new([Point::new(0., 0.), Point::new(10., 20.)]))];
+    let mut project = Project { version: 0, ..Project::new(None, &[Layer::default()], &Default::default(), &shapes) };
+    assert!(project.upgrade(Some([100, 50])));
+    assert_eq!(project.version, VERSION);
+    assert_eq!(project.shapes[0].points, [[50., -25.], [60., -5.]]);
+
+    // Current projects are left as they are
+    assert!(project.upgrade(Some([100, 50])));
+    assert_eq!(project.shapes[0].points, [[50., -25.], [60., -5.]]);
+}
+
+#[test]
+fn older_projects_without_their_image_keep_their_version() {
+    let shapes: Vec<Box<dyn Shape>> = vec![Box::new(Line::new([Point::new(0., 0.), Point::new(10., 20.)]))];
+    let mut project = Project { version: 0, ..Project::new(None, &[Layer::default()], &Default::default(), &shapes) };
+    assert!(!project.upgrade(None));
+    assert_eq!(project.version, 0);
+    assert_eq!(project.shapes[0].points, [[0., 0.], [10., 20.]]);
+}
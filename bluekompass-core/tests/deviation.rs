@@ -0,0 +1,89 @@
+//! Traced shapes must be paired with their nominal counterpart and checked against its tolerance
+
+use bluekompass_core::calibration::Calibration;
+use bluekompass_core::deviation::{compare, DeviationTolerance, DEFAULT_PAIRING_DISTANCE};
+use bluekompass_core::point::Point;
+use bluekompass_core::shapes::{Circle, Line, Shape};
+
+const TOLERANCE: f64 = 1e-9;
+
+fn circle(center: Point, radius: f64, id: usize) -> Circle {
+    let mut circle = Circle::new([
+        Point::new(center.x + radius, center.y),
+        Point::new(center.x, center.y + radius),
+        Point::new(center.x - radius, center.y),
+    ]);
+    circle.properties_mut().id = id;
+    circle
+}
+
+fn line(points: [Point; 2], id: usize) -> Line {
+    let mut line = Line::new(points);
+    line.properties_mut().id = id;
+    line
+}
+
+#[test]
+fn shapes_are_paired_with_the_nearest_nominal_shape() {
+    let nominal_left = circle(Point::new(0., 0.), 10., 1);
+    let nominal_right = circle(Point::new(100., 0.), 10., 2);
+    let nominal_line = line([Point::new(0., 50.), Point::new(100., 50.)], 3);
+    let traced_right = circle(Point::new(100.3, 0.4), 10.2, 4);
+    let traced_line = line([Point::new(0., 50.5), Point::new(100., 49.5)], 5);
+
+    let nominal: [&dyn Shape; 3] = [&nominal_left, &nominal_right, &nominal_line];
+    let traced: [&dyn Shape; 2] = [&traced_right, &traced_line];
+    let comparison = compare(&traced, &nominal, DeviationTolerance::default(), DEFAULT_PAIRING_DISTANCE, &Calibration::default());
+
+    assert_eq!(comparison.deviations.len(), 2);
+    assert_eq!(comparison.unpaired_nominal, vec![1]);
+    assert!(comparison.unpaired_traced.is_empty());
+
+    let circle = &comparison.deviations[0];
+    assert_eq!(circle.nominal_id, 2);
+    assert!((circle.position - 0.5).abs() < TOLERANCE);
+    assert!((circle.radius.unwrap() - 0.2).abs() < 1e-6);
+    assert!(circle.passed());
+
+    let line = &comparison.deviations[1];
+    assert!(line.position.abs() < TOLERANCE);
+    assert!((line.angle.unwrap() + 0.01_f64.atan().to_degrees()).abs() < TOLERANCE);
+    assert!(line.passed());
+}
+
+#[test]
+fn deviations_use_calibrated_units_and_the_tolerance_of_the_nominal_shape() {
+    let mut nominal = circle(Point::new(0., 0.), 10., 1);
+    nominal.properties_mut().deviation_tolerance = Some(DeviationTolerance { position: 0.1, radius: 1., angle: 1. });
+    let traced = circle(Point::new(3., 4.), 10., 2);
+    let calibration = Calibration::from_reference(10., 1., "mm").unwrap();
+
+    let comparison = compare(&[&traced], &[&nominal], DeviationTolerance::default(), DEFAULT_PAIRING_DISTANCE, &calibration);
+    let deviation = &comparison.deviations[0];
+    assert!((deviation.position - 0.5).abs() < TOLERANCE);
+    assert!(!deviation.passed());
+    assert_eq!(comparison.failed(), 1);
+}
+
+#[test]
+fn shapes_farther_than_the_pairing_distance_are_left_unpaired() {
+    let nominal = circle(Point::new(0., 0.), 10., 1);
+    let near = circle(Point::new(30., 40.), 10., 2);
+    let far = line([Point::new(500., 0.), Point::new(600., 0.)], 3);
+    let nominal_line = line([Point::new(0., 100.), Point::new(100., 100.)], 4);
+    let calibration = Calibration::from_reference(10., 1., "mm").unwrap();
+
+    // The circles are 5 mm apart, the lines 50 mm apart
+    let traced: [&dyn Shape; 2] = [&near, &far];
+    let nominal: [&dyn Shape; 2] = [&nominal, &nominal_line];
+    let comparison = compare(&traced, &nominal, DeviationTolerance::default(), 5., &calibration);
+    assert_eq!(comparison.deviations.len(), 1);
+    assert_eq!(comparison.deviations[0].traced_id, 2);
+    assert_eq!(comparison.unpaired_traced, vec![3]);
+    assert_eq!(comparison.unpaired_nominal, vec![4]);
+
+    let comparison = compare(&traced, &nominal, DeviationTolerance::default(), 4.9, &calibration);
+    assert!(comparison.deviations.is_empty());
+    assert_eq!(comparison.unpaired_traced, vec![2, 3]);
+    assert_eq!(comparison.unpaired_nominal, vec![1, 4]);
+}
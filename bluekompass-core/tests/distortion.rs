@@ -0,0 +1,107 @@
+//! Lens distortion fitted to a checkerboard seen through a lens
+
+mod common;
+
+use bluekompass_core::checkerboard::find_corners;
+use bluekompass_core::distortion::{calibrate, Distortion};
+use bluekompass_core::homography::Homography;
+use bluekompass_core::intensity::Intensities;
+use bluekompass_core::orientation::ImageOperation;
+use bluekompass_core::point::Point;
+
+use common::close_within;
+
+const SIZE: [usize; 2] = [480, 360];
+
+/// 8 × 6 squares, so 7 × 5 inner corners
+const SQUARES: [usize; 2] = [8, 6];
+
+fn barrel() -> Distortion {
+    Distortion { radial: [-0.12, 0.02], tangential: [0.002, -0.001], ..Distortion::none(SIZE) }
+}
+
+/// Board in squares seen in perspective, through the lens, on a white background
+fn render(board: [Point; 4], distortion: &Distortion) -> Vec<u8> {
+    let squares = [(0., 0.), (SQUARES[0] as f64, 0.), (SQUARES[0] as f64, SQUARES[1] as f64), (0., SQUARES[1] as f64)]
+        .map(|(x, y)| Point::new(x, y));
+    let to_board = Homography::from_points(board, squares).unwrap();
+    let mut rgba = Vec::with_capacity(4 * SIZE[0] * SIZE[1]);
+    for y in 0..SIZE[1] {
+        for x in 0..SIZE[0] {
+            // 3 × 3 samples per pixel
+            let mut dark = 0;
+            for sample in 0..9 {
+                let pixel = Point::new(x as f64 + (1 + 2 * (sample % 3)) as f64 / 6., y as f64 + (1 + 2 * (sample / 3)) as f64 / 6.);
+                let position = to_board.apply(distortion.undistort(pixel)).unwrap();
+                let inside = (0. ..SQUARES[0] as f64).contains(&position.x) && (0. ..SQUARES[1] as f64).contains(&position.y);
+                if inside && (position.x.floor() + position.y.floor()) as i64 % 2 == 0 {
+                    dark += 1;
+                }
+            }
+            let value = (255 - dark * 255 / 9) as u8;
+            rgba.extend([value, value, value, 255]);
+        }
+    }
+    rgba
+}
+
+fn views() -> Vec<[Point; 4]> {
+    vec![
+        [Point::new(40., 30.), Point::new(440., 50.), Point::new(430., 330.), Point::new(50., 320.)],
+        [Point::new(70., 60.), Point::new(400., 20.), Point::new(450., 340.), Point::new(30., 300.)],
+    ]
+}
+
+#[test]
+fn undistortion_inverts_the_lens() {
+    let distortion = barrel();
+    let pixel = Point::new(20., 340.);
+    let moved = distortion.distort(distortion.undistort(pixel));
+    assert!(close_within(moved, pixel, 1e-6));
+
+    let operation = ImageOperation::Undistort(distortion);
+    assert_eq!(operation.size(SIZE), SIZE);
+    let point = Point::new(100., -50.);
+    let mapped = operation.map_point(point, SIZE);
+    let unmapped = operation.unmap_point(mapped, SIZE);
+    assert!(close_within(unmapped, point, 1e-6));
+}
+
+#[test]
+fn corners_are_found_row_by_row() {
+    let board = views()[0];
+    let rgba = render(board, &Distortion::none(SIZE));
+    let corners = find_corners(&Intensities::new(SIZE[0], SIZE[1], &rgba).unwrap(), [7, 5]).unwrap();
+    assert_eq!(corners.len(), 35);
+
+    // Without distortion the inner corners are the board seen in perspective, in one of its orientations
+    let squares = [(0., 0.), (8., 0.), (8., 6.), (0., 6.)].map(|(x, y)| Point::new(x, y));
+    let to_image = Homography::from_points(squares, board).unwrap();
+    let expected: Vec<Point> = (0..35).map(|index| to_image.apply(Point::new((index % 7 + 1) as f64, (index / 7 + 1) as f64)).unwrap()).collect();
+    let close = |corners: &[Point]| corners.iter().zip(&expected).all(|(a, b)| (a.x - b.x).hypot(a.y - b.y) < 0.2);
+    let reversed: Vec<Point> = corners.iter().rev().copied().collect();
+    assert!(close(&corners) || close(&reversed));
+
+    assert!(find_corners(&Intensities::new(SIZE[0], SIZE[1], &rgba).unwrap(), [9, 6]).is_none());
+}
+
+#[test]
+fn distortion_is_recovered_from_the_corners() {
+    let lens = barrel();
+    let views: Vec<Vec<Point>> = views().into_iter()
+        .map(|board| {
+            let rgba = render(board, &lens);
+            find_corners(&Intensities::new(SIZE[0], SIZE[1], &rgba).unwrap(), [7, 5]).unwrap()
+        })
+        .collect();
+    let (distortion, residual) = calibrate(&views, [7, 5], SIZE).unwrap();
+    assert!(residual < 0.2, "residual {residual}");
+    assert!((distortion.radial[0] - lens.radial[0]).abs() < 0.01);
+    assert!(distortion.tangential.iter().zip(lens.tangential).all(|(a, b)| (a - b).abs() < 5e-4));
+
+    // Same correction where the boards were seen
+    for pixel in [Point::new(60., 60.), Point::new(420., 200.), Point::new(240., 320.)] {
+        let [a, b] = [distortion.undistort(pixel), lens.undistort(pixel)];
+        assert!((a.x - b.x).hypot(a.y - b.y) < 1.);
+    }
+}
@@ -0,0 +1,61 @@
+//! Measurement exports must use calibrated units and stay readable by spreadsheets
+
+use bluekompass_core::calibration::Calibration;
+use bluekompass_core::export::{shape_rows, to_csv, to_json, to_svg, to_tsv, SvgImage};
+use bluekompass_core::layers::Layer;
+use bluekompass_core::orientation::ImageOperation;
+use bluekompass_core::point::Point;
+use bluekompass_core::shapes::{Circle, Line, Shape};
+
+fn shapes() -> Vec<Box<dyn Shape>> {
+    let mut line = Line::new([Point::new(0., 0.), Point::new(30., 40.)]);
+    line.properties_mut().name = "Edge, top".to_string();
+    let circle = Circle::new([Point::new(10., 0.), Point::new(0., 10.), Point::new(-10., 0.)]);
+    vec![Box::new(line), Box::new(circle)]
+}
+
+#[test]
+fn rows_use_calibrated_units() {
+    let calibration = Calibration::from_reference(50., 5., "mm").unwrap();
+    let rows = shape_rows(&shapes(), &[Layer::default()], &calibration);
+
+    assert_eq!(rows[0].length, Some(5.));
+    assert_eq!(rows[0].points[1], [3., 4.]);
+    assert!(rows[0].radius.is_none());
+    assert!((rows[1].radius.unwrap() - 1.).abs() < 1e-12);
+    assert!((rows[1].area.unwrap() - std::f64::consts::PI).abs() < 1e-12);
+    assert_eq!(rows[1].unit, "mm");
+}
+
+#[test]
+fn text_exports_have_one_row_per_shape() {
+    let rows = shape_rows(&shapes(), &[Layer::default()], &Calibration::default());
+
+    let csv = to_csv(&rows);
+    assert_eq!(csv.lines().count(), 3);
+    assert!(csv.contains("\"Edge, top\""));
+    assert!(to_tsv(&rows).lines().nth(1).unwrap().starts_with("0\tLine\tEdge, top\tDefault\t"));
+
+    let json: serde_json::Value = serde_json::from_str(&to_json(&rows).unwrap()).unwrap();
+    assert_eq!(json[1]["type"], "Circle");
+    assert_eq!(json[0]["length"], 50.);
+}
+
+#[test]
+fn svg_of_a_rotated_image_embeds_the_rotated_pixels_under_the_shapes() {
+    // Line from the top left corner of a 40 × 20 image, the image is then turned clockwise
+    let operation = ImageOperation::RotateClockwise;
+    let size = [40, 20];
+    let mut line: Box<dyn Shape> = Box::new(Line::new([Point::new(0., 0.), Point::new(10., -5.)]));
+    line.map_points(&|point| operation.map_point(point, size));
+
+    let png = b"rotated pixels";
+    let svg = to_svg(&[line], &[Layer::default()], operation.size(size), Some(SvgImage::Png(png)));
+    assert!(svg.contains(r#"width="20" height="40" viewBox="0 0 20 40""#));
+    assert!(svg.contains(r#"<image xlink:href="data:image/png;base64,cm90YXRlZCBwaXhlbHM=" x="0" y="0" width="20" height="40"/>"#));
+    // The top left corner is now the top right one
+    assert!(svg.contains(r#"x1="20" y1="0" x2="15" y2="10""#), "{svg}");
+
+    let linked = to_svg(&[], &[], size, Some(SvgImage::File(std::path::Path::new("part <1>.png"))));
+    assert!(linked.contains(r#"xlink:href="part &lt;1&gt;.png""#));
+}
@@ -0,0 +1,42 @@
+//! Readouts are expressed in the active datum frame
+
+mod common;
+
+use bluekompass_core::calibration::Calibration;
+use bluekompass_core::coordinates::{Frame, YAxis};
+use bluekompass_core::measure::measure;
+use bluekompass_core::point::Point;
+use bluekompass_core::shapes::{Line, Shape};
+
+use common::close;
+
+/// Frame at (10, 10) with its X axis along the plot Y axis, 2 units per pixel
+fn calibration() -> Calibration {
+    let frame = Frame::new("Part", Point::new(10., 10.), [Point::new(0., 0.), Point::new(0., 5.)]).unwrap();
+    Calibration { frames: vec![frame], active_frame: Some(0), ..Calibration::from_reference(1., 2., "mm").unwrap() }
+}
+
+#[test]
+fn points_are_relative_to_the_active_frame() {
+    let mut calibration = calibration();
+    assert!(close(calibration.point(Point::new(10., 13.)), Point::new(6., 0.)));
+    assert!(close(calibration.point(Point::new(7., 10.)), Point::new(0., 6.)));
+
+    calibration.y_axis = YAxis::Down;
+    assert!(close(calibration.point(Point::new(7., 10.)), Point::new(0., -6.)));
+    let plot = Point::new(3., -4.);
+    assert!(close(calibration.plot_point(calibration.point(plot)), plot));
+
+    calibration.active_frame = None;
+    assert!(close(calibration.point(Point::new(7., 10.)), Point::new(14., -20.)));
+}
+
+#[test]
+fn angles_are_measured_from_the_frame_x_axis() {
+    let shapes: Vec<Box<dyn Shape>> = vec![Box::new(Line::new([Point::new(0., 0.), Point::new(-1., 0.)]))];
+    let angle = &measure(&shapes, &calibration())[1];
+    assert_eq!(angle.name, "Angle");
+    assert!((angle.value - 90.).abs() < 1e-9);
+
+    assert!(Frame::new("Degenerate", Point::new(0., 0.), [Point::new(1., 1.); 2]).is_none());
+}
@@ -0,0 +1,74 @@
+//! Perspective rectification from the four corners of a known rectangle and optional check points
+
+mod common;
+
+use bluekompass_core::homography::Homography;
+use bluekompass_core::orientation::{rectification, ImageOperation};
+use bluekompass_core::point::Point;
+
+use common::{close, close_within};
+
+/// Rectangle seen at an angle, top left, top right, bottom right, bottom left
+fn corners() -> [Point; 4] {
+    [Point::new(120., 80.), Point::new(520., 110.), Point::new(480., 390.), Point::new(90., 340.)]
+}
+
+#[test]
+fn homographies_move_the_four_points_and_can_be_inverted() {
+    let to = [Point::new(0., 0.), Point::new(200., 0.), Point::new(200., 100.), Point::new(0., 100.)];
+    let homography = Homography::from_points(corners(), to).unwrap();
+    for (from, to) in corners().iter().zip(&to) {
+        assert!(close(homography.apply(*from).unwrap(), *to));
+    }
+
+    let inverse = homography.inverse().unwrap();
+    let point = Point::new(300., 200.);
+    assert!(close(inverse.apply(homography.apply(point).unwrap()).unwrap(), point));
+
+    // Three aligned points
+    let aligned = [Point::new(0., 0.), Point::new(1., 1.), Point::new(2., 2.), Point::new(0., 1.)];
+    assert!(Homography::from_points(aligned, to).is_none());
+}
+
+#[test]
+fn rectified_corners_make_a_rectangle_of_the_given_proportions() {
+    let rectified = rectification(corners(), [200., 100.], &[], [640, 480]).unwrap();
+    // Four points are fitted exactly
+    assert!(rectified.residual < 1e-9);
+    let ImageOperation::Rectify { homography, size } = rectified.operation else {
+        panic!("not a rectification");
+    };
+    let [a, b, c, d] = corners().map(|corner| homography.apply(corner).unwrap());
+    let (width, height) = (200. * rectified.scale, 100. * rectified.scale);
+    assert!(close_within(b, Point::new(a.x + width, a.y), 1e-6));
+    assert!(close_within(c, Point::new(a.x + width, a.y + height), 1e-6));
+    assert!(close_within(d, Point::new(a.x, a.y + height), 1e-6));
+    // The rectangle is inside the new image
+    assert!(a.x >= 0. && a.y >= 0. && c.x <= size[0] as f64 && c.y <= size[1] as f64);
+
+    assert!(rectification(corners(), [0., 100.], &[], [640, 480]).is_none());
+}
+
+#[test]
+fn check_points_are_fitted_by_least_squares_and_give_the_residual() {
+    let to = [Point::new(0., 0.), Point::new(200., 0.), Point::new(200., 100.), Point::new(0., 100.)];
+    let exact = Homography::from_points(corners(), to).unwrap();
+    let inverse = exact.inverse().unwrap();
+
+    // Points that agree with the corners give the same homography
+    let positions = [Point::new(100., 50.), Point::new(50., 20.), Point::new(170., 90.)];
+    let pixels = positions.map(|position| inverse.apply(position).unwrap());
+    let from: Vec<Point> = corners().into_iter().chain(pixels).collect();
+    let targets: Vec<Point> = to.into_iter().chain(positions).collect();
+    let fitted = Homography::fit(&from, &targets).unwrap();
+    assert!(fitted.0.iter().zip(exact.0).all(|(a, b)| (a - b).abs() < 1e-9 * b.abs().max(1.)));
+    assert!(fitted.residual(&from, &targets).unwrap() < 1e-9);
+    assert!(Homography::fit(&from[..3], &targets[..3]).is_none());
+
+    // A check point off by a few pixels shows in the residual, in pixels of the rectified image
+    let checks = [(Point::new(pixels[0].x + 4., pixels[0].y - 3.), positions[0]), (pixels[1], positions[1]), (pixels[2], positions[2])];
+    let rectified = rectification(corners(), [200., 100.], &checks, [640, 480]).unwrap();
+    assert!(rectified.residual > 0.1 && rectified.residual < 5. * rectified.scale, "residual {}", rectified.residual);
+    let agreeing = positions.map(|position| (inverse.apply(position).unwrap(), position));
+    assert!(rectification(corners(), [200., 100.], &agreeing, [640, 480]).unwrap().residual < 1e-6);
+}
@@ -0,0 +1,48 @@
+//! Shapes of a project measured on other images
+
+use bluekompass_core::calibration::{Resolution, ResolutionUnit};
+use bluekompass_core::layers::Layer;
+use bluekompass_core::measure::measure;
+use bluekompass_core::orientation::ImageOperation;
+use bluekompass_core::point::Point;
+use bluekompass_core::project::Project;
+use bluekompass_core::shapes::{Line, Shape};
+
+fn project() -> Project {
+    let shapes: Vec<Box<dyn Shape>> = vec![Box::new(Line::new([Point::new(10., -10.), Point::new(40., -10.)]))];
+    Project::new(None, &[Layer::default()], &Default::default(), &shapes)
+}
+
+#[test]
+fn images_of_other_resolutions_give_other_lengths() {
+    let project = project();
+    let dpi = |dpi| Some(Resolution { x: dpi, y: dpi, unit: ResolutionUnit::Inch });
+    let lengths: Vec<f64> = [dpi(300.), dpi(150.)].into_iter()
+        .map(|resolution| {
+            let (shapes, calibration) = project.on_image(Some([100, 50]), [100, 50], resolution);
+            assert_eq!(calibration.unit, "mm");
+            measure(&shapes, &calibration)[0].value
+        })
+        .collect();
+    assert!((lengths[0] - 30. * 25.4 / 300.).abs() < 1e-9);
+    assert!((lengths[1] - 2. * lengths[0]).abs() < 1e-9);
+
+    // A calibrated project keeps its scale
+    let mut calibrated = project;
+    calibrated.calibration.scale = 0.5;
+    calibrated.calibration.unit = "mm".to_string();
+    let (shapes, calibration) = calibrated.on_image(Some([100, 50]), [100, 50], dpi(300.));
+    assert!((measure(&shapes, &calibration)[0].value - 15.).abs() < 1e-9);
+}
+
+#[test]
+fn shapes_follow_the_operations_on_images_of_another_size() {
+    let mut project = project();
+    project.image_operations = vec![ImageOperation::FlipHorizontal];
+    let (same, _) = project.on_image(Some([100, 50]), [100, 50], None);
+    assert_eq!(same[0].as_slice()[0], Point::new(10., -10.));
+
+    // Flipped around the middle of the wider image, the line stays as far from its right edge
+    let (wider, _) = project.on_image(Some([100, 50]), [120, 50], None);
+    assert_eq!(wider[0].as_slice(), [Point::new(30., -10.), Point::new(60., -10.)]);
+}
@@ -0,0 +1,134 @@
+//! DXF drawings must be imported as shapes that can be aligned on the image
+
+mod common;
+
+use bluekompass_core::calibration::Calibration;
+use bluekompass_core::coordinates::{Frame, YAxis};
+use bluekompass_core::export::to_dxf;
+use bluekompass_core::import::from_dxf;
+use bluekompass_core::layers::Layer;
+use bluekompass_core::point::Point;
+use bluekompass_core::shapes::{Circle, Geometry, Line, Shape};
+use bluekompass_core::transform::Similarity;
+
+use common::close_within;
+
+const TOLERANCE: f64 = 1e-9;
+
+fn entities(body: &str) -> String {
+    format!("0\nSECTION\n2\nENTITIES\n{body}0\nENDSEC\n0\nEOF\n")
+}
+
+#[test]
+fn exported_shapes_are_imported_back() {
+    let shapes: Vec<Box<dyn Shape>> = vec![
+        Box::new(Line::new([Point::new(-5., 2.), Point::new(7., 11.)])),
+        Box::new(Circle::new([Point::new(10., 0.), Point::new(0., 10.), Point::new(-10., 0.)])),
+    ];
+    let imported = from_dxf(&to_dxf(&shapes, &[Layer::default()], &Calibration::default())).unwrap().shapes;
+
+    assert_eq!(imported.len(), 2);
+    let Some(Geometry::Line([a, b])) = imported[0].geometry() else { panic!("expected a line") };
+    assert_eq!((a, b), (Point::new(-5., 2.), Point::new(7., 11.)));
+    let Some(Geometry::Circle { center, radius }) = imported[1].geometry() else { panic!("expected a circle") };
+    assert!(center.x.abs() < TOLERANCE && center.y.abs() < TOLERANCE);
+    assert!((radius - 10.).abs() < TOLERANCE);
+}
+
+#[test]
+fn imported_shapes_go_back_where_they_were_exported_from() {
+    let line = [Point::new(40., -25.), Point::new(90., -60.)];
+    for y_axis in [YAxis::Up, YAxis::Down] {
+        let calibration = Calibration {
+            scale: 0.1,
+            unit: "mm".to_string(),
+            y_axis,
+            frames: vec![Frame::new("Datum", Point::new(10., -5.), [Point::new(0., 0.), Point::new(3., 1.)]).unwrap()],
+            active_frame: Some(0),
+        };
+        let shapes: Vec<Box<dyn Shape>> = vec![Box::new(Line::new(line))];
+        let mut imported = from_dxf(&to_dxf(&shapes, &[Layer::default()], &calibration)).unwrap().shapes;
+        imported[0].map_points(&|point| calibration.unframe_point(point));
+
+        let Some(Geometry::Line([a, b])) = imported[0].geometry() else { panic!("expected a line") };
+        for (point, target) in [a, b].iter().zip(line) {
+            assert!(close_within(*point, target, TOLERANCE), "{y_axis:?}");
+        }
+    }
+}
+
+/// Ends of the imported lines, in order
+fn polyline(shapes: &[Box<dyn Shape>]) -> Vec<[Point; 2]> {
+    shapes.iter()
+        .map(|shape| match shape.geometry() {
+            Some(Geometry::Line(ends)) => ends,
+            _ => panic!("expected lines"),
+        })
+        .collect()
+}
+
+fn on_circle(point: Point, center: Point, radius: f64) -> bool {
+    ((point.x - center.x).hypot(point.y - center.y) - radius).abs() < TOLERANCE
+}
+
+#[test]
+fn arcs_follow_their_extent() {
+    let dxf = entities("0\nARC\n8\n0\n10\n1.0\n20\n2.0\n40\n3.0\n50\n270.0\n51\n90.0\n0\nTEXT\n8\n0\n10\n0\n20\n0\n1\nignored\n");
+    let import = from_dxf(&dxf).unwrap();
+    assert_eq!((import.approximated, import.skipped), (1, vec!["TEXT".to_string()]));
+
+    // Counterclockwise from the bottom to the top of the circle, through its right side only
+    let lines = polyline(&import.shapes);
+    assert_eq!(lines.len(), 36);
+    let center = Point::new(1., 2.);
+    assert!(lines.iter().flatten().all(|&point| on_circle(point, center, 3.) && point.x > 1. - TOLERANCE));
+    let (start, end) = (lines[0][0], lines[35][1]);
+    assert!((start.x - 1.).abs() < TOLERANCE && (start.y + 1.).abs() < TOLERANCE);
+    assert!((end.x - 1.).abs() < TOLERANCE && (end.y - 5.).abs() < TOLERANCE);
+    assert!(lines.windows(2).all(|pair| pair[0][1] == pair[1][0]));
+}
+
+#[test]
+fn bulged_polyline_segments_are_arcs() {
+    // Half circle below the first segment, counterclockwise, then a straight closing path
+    let dxf = entities(
+        "0\nLWPOLYLINE\n8\n0\n90\n3\n70\n1\n10\n0\n20\n0\n42\n1.0\n10\n2\n20\n0\n10\n2\n20\n3\n",
+    );
+    let import = from_dxf(&dxf).unwrap();
+    assert_eq!(import.approximated, 1);
+    assert!(import.skipped.is_empty());
+
+    let lines = polyline(&import.shapes);
+    let (arc, straight) = lines.split_at(lines.len() - 2);
+    assert_eq!(arc.len(), 36);
+    assert!(arc.iter().flatten().all(|&point| on_circle(point, Point::new(1., 0.), 1.) && point.y < TOLERANCE));
+    assert!(arc.iter().flatten().any(|point| (point.x - 1.).abs() < TOLERANCE && (point.y + 1.).abs() < TOLERANCE));
+    assert_eq!(arc[0][0], Point::new(0., 0.));
+    assert_eq!(straight, [[Point::new(2., 0.), Point::new(2., 3.)], [Point::new(2., 3.), Point::new(0., 0.)]]);
+
+    // A negative bulge goes clockwise, above the segment
+    let dxf = entities("0\nLWPOLYLINE\n8\n0\n90\n2\n70\n0\n10\n0\n20\n0\n42\n-1.0\n10\n2\n20\n0\n");
+    let lines = polyline(&from_dxf(&dxf).unwrap().shapes);
+    assert!(lines.iter().flatten().all(|&point| on_circle(point, Point::new(1., 0.), 1.) && point.y > -TOLERANCE));
+}
+
+#[test]
+fn truncated_files_are_refused() {
+    assert!(from_dxf("0\nSECTION\n2").is_err());
+    assert!(from_dxf("zero\nSECTION\n").is_err());
+}
+
+#[test]
+fn two_point_alignment_maps_both_points() {
+    let from = [Point::new(0., 0.), Point::new(10., 0.)];
+    let to = [Point::new(100., 50.), Point::new(100., 70.)];
+    let similarity = Similarity::from_two_points(from, to).unwrap();
+    assert!((similarity.scale - 2.).abs() < TOLERANCE);
+
+    let mut line: Box<dyn Shape> = Box::new(Line::new(from));
+    line.map_points(&|point| similarity.apply(point));
+    for (point, target) in line.as_slice().iter().zip(to) {
+        assert!(close_within(*point, target, TOLERANCE));
+    }
+    assert!(Similarity::from_two_points([from[0], from[0]], to).is_none());
+}
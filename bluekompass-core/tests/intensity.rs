@@ -0,0 +1,40 @@
+//! Intensities are read from the original pixels, at subpixel positions
+
+use bluekompass_core::intensity::{edge_crossings, Intensities, Region};
+use bluekompass_core::point::Point;
+
+/// Gray pixels of the given values, in one row
+fn row(values: &[u8]) -> Vec<u8> {
+    values.iter().flat_map(|&value| [value, value, value, 255]).collect()
+}
+
+#[test]
+fn samples_are_interpolated_between_pixel_centers() {
+    let rgba = row(&[0, 100]);
+    let intensities = Intensities::new(2, 1, &rgba).unwrap();
+    assert_eq!(intensities.sample(Point::new(0.5, 0.5)), Some(0.));
+    assert_eq!(intensities.sample(Point::new(1., 0.5)), Some(50.));
+    assert_eq!(intensities.sample(Point::new(2., 0.5)), Some(100.));
+    assert_eq!(intensities.sample(Point::new(2.5, 0.5)), None);
+}
+
+#[test]
+fn histogram_counts_pixels_of_the_region() {
+    let rgba = row(&[0, 0, 255, 255]);
+    let intensities = Intensities::new(4, 1, &rgba).unwrap();
+    let histogram = intensities.histogram(Region::All);
+    assert_eq!((histogram.luma[0], histogram.luma[255]), (2, 2));
+
+    let histogram = intensities.histogram(Region::Rectangle { min: Point::new(1., 0.), max: Point::new(3., 1.) });
+    assert_eq!((histogram.red[0], histogram.red[255]), (1, 1));
+}
+
+#[test]
+fn edges_are_found_at_half_intensity() {
+    let rgba = row(&[0, 0, 200, 200, 200, 0]);
+    let intensities = Intensities::new(6, 1, &rgba).unwrap();
+    let profile = intensities.profile([Point::new(0.5, 0.5), Point::new(5.5, 0.5)]);
+    assert_eq!(profile.len(), 6);
+    let crossings = edge_crossings(&profile);
+    assert_eq!(crossings, [1.5, 4.5]);
+}
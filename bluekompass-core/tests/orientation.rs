@@ -0,0 +1,49 @@
+//! Shapes follow the pixels when the image is rotated or flipped
+
+mod common;
+
+use bluekompass_core::coordinates::pixel_to_plot;
+use bluekompass_core::orientation::{straightening_rotation, ImageOperation};
+use bluekompass_core::point::Point;
+
+use common::close;
+
+/// 2×1 image of a black then a white pixel
+fn image() -> Vec<u8> {
+    vec![0, 0, 0, 255, 255, 255, 255, 255]
+}
+
+#[test]
+fn quarter_turns_and_flips_move_pixels_and_points_together() {
+    let operation = ImageOperation::RotateClockwise;
+    let (size, rgba) = operation.apply([2, 1], &image());
+    assert_eq!(size, [1, 2]);
+    assert_eq!(rgba, [0, 0, 0, 255, 255, 255, 255, 255]);
+    // Center of the white pixel, now the bottom one
+    let white = operation.map_point(pixel_to_plot(Point::new(1.5, 0.5)), [2, 1]);
+    assert!(close(white, pixel_to_plot(Point::new(0.5, 1.5))));
+
+    let (_, rgba) = ImageOperation::FlipHorizontal.apply([2, 1], &image());
+    assert_eq!(rgba, [255, 255, 255, 255, 0, 0, 0, 255]);
+}
+
+#[test]
+fn rotations_enlarge_the_image_and_can_be_undone() {
+    let operation = ImageOperation::Rotate(30.);
+    let size = [100, 50];
+    let [width, height] = operation.size(size);
+    assert_eq!([width, height], [112, 94]);
+    assert_eq!(ImageOperation::Rotate(90.).size(size), [50, 100]);
+
+    let point = Point::new(12., -34.);
+    let mapped = operation.map_point(point, size);
+    assert!(close(operation.unmap_point(mapped, size), point));
+}
+
+#[test]
+fn lines_are_straightened_by_the_smallest_rotation() {
+    assert!((straightening_rotation(10., false) + 10.).abs() < 1e-12);
+    assert!((straightening_rotation(170., false) - 10.).abs() < 1e-12);
+    assert!((straightening_rotation(80., true) - 10.).abs() < 1e-12);
+    assert!((straightening_rotation(-80., true) + 10.).abs() < 1e-12);
+}
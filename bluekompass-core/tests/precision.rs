@@ -0,0 +1,74 @@
+//! Geometry must not lose precision for coordinates of large, high resolution images
+
+use bluekompass_core::layers::Layer;
+use bluekompass_core::point::{Point, Vec2};
+use bluekompass_core::project::Project;
+use bluekompass_core::shapes::{Circle, Geometry, Line, Select, Shape};
+use bluekompass_core::style::Style;
+
+const TOLERANCE: f64 = 1e-9;
+
+fn point_on_circle(center: Point, radius: f64, angle: f64) -> Point {
+    Point::new(center.x + radius * angle.cos(), center.y + radius * angle.sin())
+}
+
+#[test]
+fn circle_center_and_radius_far_from_origin() {
+    let center = Point::new(40_000.25, -35_000.75);
+    let radius = 1_234.567_8;
+    let points = [0.3, 2.1, 4.4].map(|angle| point_on_circle(center, radius, angle));
+
+    let circle = Circle::new(points);
+    let Geometry::Circle { center: computed, radius: computed_radius } = circle.geometry().unwrap() else {
+        panic!("a circle must have a circle geometry");
+    };
+    assert!((computed.x - center.x).abs() < TOLERANCE, "{computed:?}");
+    assert!((computed.y - center.y).abs() < TOLERANCE, "{computed:?}");
+    assert!((computed_radius - radius).abs() < TOLERANCE, "{computed_radius}");
+}
+
+#[test]
+fn small_circle_far_from_origin() {
+    // A micrometer sized feature on a large calibrated image
+    let center = Point::new(65_535.5, 48_000.125);
+    let radius = 0.003;
+    let points = [0.0, 1.5, 3.5].map(|angle| point_on_circle(center, radius, angle));
+
+    let circle = Circle::new(points);
+    let (_, diameter) = circle.measurements()[1];
+    assert!((diameter - 2. * radius).abs() < 1e-6 * radius, "{diameter}");
+}
+
+#[test]
+fn line_length_and_angle_far_from_origin() {
+    let line = Line::new([Point::new(30_000.125, 20_000.5), Point::new(30_000.375, 20_000.75)]);
+    assert!((line.length() - 0.25 * 2_f64.sqrt()).abs() < TOLERANCE);
+    assert!((line.angle() - 45.).abs() < TOLERANCE);
+}
+
+#[test]
+fn distance_to_line_far_from_origin() {
+    let line = Line::new([Point::new(40_000., 40_000.), Point::new(40_010., 40_000.)]);
+    let distance = line.select_from_point(Vec2::new(40_005., 40_000.001));
+    assert!((distance - 0.001).abs() < TOLERANCE, "{distance}");
+}
+
+#[test]
+fn translation_keeps_sub_pixel_offsets() {
+    let mut line: Box<dyn Shape> = Box::new(Line::new([Point::new(50_000., 50_000.), Point::new(50_100., 50_000.)]));
+    line.translate(Vec2::new(0.001, -0.001));
+    let [a, _] = [line.as_slice()[0], line.as_slice()[1]];
+    assert!((a.x - 50_000.001).abs() < TOLERANCE, "{a:?}");
+    assert!((a.y - 49_999.999).abs() < TOLERANCE, "{a:?}");
+}
+
+#[test]
+fn project_round_trip_is_exact() {
+    let points = [Point::new(12_345.678_901_234, -98_765.432_109_876), Point::new(0.1, 0.2)];
+    let shapes: Vec<Box<dyn Shape>> = vec![Box::new(Line::new(points))];
+    let project = Project::new(None, &[Layer::default()], &Style::default(), &shapes);
+
+    let json = serde_json::to_string(&project).unwrap();
+    let loaded: Project = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded.shapes()[0].as_slice(), &points);
+}
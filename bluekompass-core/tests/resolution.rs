@@ -0,0 +1,38 @@
+//! Calibration from the resolution stored in image files
+
+use bluekompass_core::calibration::{Resolution, ResolutionUnit};
+
+#[test]
+fn resolutions_give_the_size_of_a_pixel() {
+    let inch = Resolution { x: 300., y: 300., unit: ResolutionUnit::Inch };
+    let [x, _] = inch.pixel_size().unwrap();
+    assert!((x - 25.4 / 300.).abs() < 1e-12);
+    assert!((inch.dpi().unwrap()[1] - 300.).abs() < 1e-9);
+
+    // PNG files give pixels per meter
+    let meter = Resolution { x: 11811., y: 11811., unit: ResolutionUnit::Meter };
+    let calibration = meter.calibration().unwrap();
+    assert_eq!(calibration.unit, "mm");
+    assert!((calibration.scale - 1000. / 11811.).abs() < 1e-12);
+
+}
+
+#[test]
+fn pixels_that_are_not_square_give_no_calibration() {
+    let centimeter = Resolution { x: 200., y: 100., unit: ResolutionUnit::Centimeter };
+    assert!(!centimeter.is_square());
+    assert!(centimeter.calibration().is_none());
+    assert!(centimeter.pixel_size().is_some());
+
+    // Rounding of the stored values is within the tolerance
+    let rounded = Resolution { x: 11811., y: 11812., unit: ResolutionUnit::Meter };
+    assert!(rounded.is_square());
+    assert!((rounded.calibration().unwrap().scale - 1000. / 11811.).abs() < 1e-12);
+}
+
+#[test]
+fn null_resolutions_are_ignored() {
+    let zero = Resolution { x: 0., y: 300., unit: ResolutionUnit::Inch };
+    assert!(zero.pixel_size().is_none());
+    assert!(zero.calibration().is_none());
+}
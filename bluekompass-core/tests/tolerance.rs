@@ -0,0 +1,42 @@
+//! Tolerances apply to calibrated measurements, the same way in the application and the batch command
+
+use bluekompass_core::calibration::Calibration;
+use bluekompass_core::measure::{measure, shape_passed};
+use bluekompass_core::point::Point;
+use bluekompass_core::shapes::{Line, Shape};
+use bluekompass_core::tolerance::Tolerance;
+
+fn line() -> Box<dyn Shape> {
+    Box::new(Line::new([Point::new(0., 0.), Point::new(300., 400.)]))
+}
+
+#[test]
+fn measurements_are_calibrated_and_checked() {
+    let mut shape = line();
+    shape.properties_mut().tolerances.insert("Length".to_string(), Tolerance::new(50., 0.1));
+    let calibration = Calibration::from_reference(10., 1., "mm").unwrap();
+
+    let measurements = measure(&[shape], &calibration);
+    let length = &measurements[0];
+    assert_eq!((length.name, length.unit.as_str()), ("Length", "mm"));
+    assert!((length.value - 50.).abs() < 1e-9);
+    assert_eq!(length.passed(), Some(true));
+
+    let angle = &measurements[1];
+    assert_eq!(angle.unit, "°");
+    assert_eq!(angle.passed(), None);
+}
+
+#[test]
+fn a_shape_fails_when_any_measurement_fails() {
+    let mut shape = line();
+    assert_eq!(shape_passed(shape.as_ref(), &Calibration::default()), None);
+
+    let tolerances = &mut shape.properties_mut().tolerances;
+    tolerances.insert("Length".to_string(), Tolerance::new(500., 1.));
+    tolerances.insert("Angle".to_string(), Tolerance { nominal: 45., lower: -1., upper: 1. });
+    assert_eq!(shape_passed(shape.as_ref(), &Calibration::default()), Some(false));
+
+    shape.properties_mut().tolerances.insert("Angle".to_string(), Tolerance::new(53.13, 0.01));
+    assert_eq!(shape_passed(shape.as_ref(), &Calibration::default()), Some(true));
+}
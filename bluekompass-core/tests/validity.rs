@@ -0,0 +1,45 @@
+//! Degenerate defining points must be reported instead of producing NaN geometry
+
+use bluekompass_core::point::{Point, Vec2};
+use bluekompass_core::shapes::{build_shape, Circle, Line, Select, Shape, ShapeKind};
+
+#[test]
+fn collinear_points_make_an_invalid_circle() {
+    let circle = Circle::new([Point::new(0., 0.), Point::new(1., 1.), Point::new(2., 2.)]);
+    assert!(!circle.is_valid());
+    assert!(circle.geometry().is_none());
+    assert!(circle.measurements().is_empty());
+    assert_eq!(circle.select_from_point(Vec2::new(1., 1.)), f64::INFINITY);
+}
+
+#[test]
+fn coincident_points_make_an_invalid_circle() {
+    let point = Point::new(3., 4.);
+    for points in [[point, point, Point::new(5., 6.)], [point, Point::new(5., 6.), point]] {
+        assert!(!Circle::new(points).is_valid());
+    }
+}
+
+#[test]
+fn nearly_collinear_points_far_from_origin_make_a_valid_circle() {
+    let circle = Circle::new([Point::new(40_000., 40_000.), Point::new(40_010., 40_000.01), Point::new(40_020., 40_000.)]);
+    assert!(circle.is_valid());
+    assert!(circle.select_from_point(Vec2::new(40_000., 40_000.)).abs() < 1e-6);
+}
+
+#[test]
+fn zero_length_line_is_invalid() {
+    let line = Line::new([Point::new(7., 7.), Point::new(7., 7.)]);
+    assert!(!line.is_valid());
+    assert!(line.measurements().is_empty());
+}
+
+#[test]
+fn degenerate_replacement_is_refused() {
+    let points = [Point::new(0., 0.), Point::new(1., 1.), Point::new(2., 0.)];
+    let mut circle = build_shape(ShapeKind::Circle, &points).unwrap();
+    assert!(!circle.try_replace(1, Point::new(1., 0.)));
+    assert_eq!(circle.as_slice(), &points);
+    assert!(circle.try_replace(1, Point::new(1., 2.)));
+    assert!(circle.is_valid());
+}
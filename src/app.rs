@@ -1,14 +1,25 @@
 use super::image_loader::BlueKompassImage;
 use eframe::egui;
-use egui_plot::{Plot, PlotBounds};
+use egui_plot::{Plot, PlotBounds, PlotPoint, PlotUi};
 
-use egui::{Button, Image, ImageSource, Layout, include_image};
+use egui::{Button, Image, ImageSource, Layout, Vec2b, include_image};
 
 use egui_file::FileDialog;
 use std::path::PathBuf;
 
-use crate::shapes::Shape;
+use bluekompass_core::adjustments::Adjustments;
+use bluekompass_core::calibration::Calibration;
+use bluekompass_core::deviation::{DeviationTolerance, DEFAULT_PAIRING_DISTANCE};
+use bluekompass_core::intensity::{Histogram, Region};
+use bluekompass_core::layers::Layer;
+use bluekompass_core::orientation::ImageOperation;
+use bluekompass_core::point::Point;
+use bluekompass_core::project::VERSION;
+use bluekompass_core::shapes::{Shape, ShapeKind};
+use bluekompass_core::style::Style;
+use crate::keymap::{Action, Keymap};
 use crate::builders::{Builder, Line, Circle};
+use crate::raster::RasterOptions;
 
 mod selection;
 mod build;
@@ -16,9 +27,29 @@ mod image;
 mod draw;
 mod update;
 mod remove;
+mod outliner;
+mod layers;
+mod file;
+mod project;
+mod style;
+mod shortcuts;
+mod calibration;
+mod reference;
+mod comparison;
+mod tolerance;
+mod adjustments;
+mod intensity;
+mod status;
+mod loupe;
+mod frames;
+mod orientation;
+mod rectify;
+mod lens;
+mod info;
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(PartialEq)]
-enum Mode {
+pub enum Mode {
     DRAG,
     SELECTION,
     LINE,
@@ -26,11 +57,64 @@ enum Mode {
     //SPLINE,
 }
 
-const MODES: [(Mode, &str, ImageSource); 4] = [
-    (Mode::DRAG, "Drag", include_image!("../assets/hand.png")),
-    (Mode::SELECTION, "Selection", include_image!("../assets/cursor.png")),
-    (Mode::LINE, "Line", include_image!("../assets/line.png")),
-    (Mode::CIRCLE, "Circle", include_image!("../assets/circle.png")),
+impl Mode {
+    pub const NAMES: [&'static str; 4] = ["drag", "selection", "line", "circle"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "drag" => Some(Mode::DRAG),
+            "selection" => Some(Mode::SELECTION),
+            "line" => Some(Mode::LINE),
+            "circle" => Some(Mode::CIRCLE),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mode::DRAG => Self::NAMES[0],
+            Mode::SELECTION => Self::NAMES[1],
+            Mode::LINE => Self::NAMES[2],
+            Mode::CIRCLE => Self::NAMES[3],
+        }
+    }
+}
+
+/// Options given on the command line when starting the application
+#[derive(Default)]
+pub struct StartupOptions {
+    pub file: Option<PathBuf>,
+    pub mode: Option<Mode>,
+    pub zoom_to_fit: bool,
+}
+
+#[derive(Clone, Copy)]
+enum Dragging {
+    Point { shape_index: usize, point_index: usize },
+    Shape { shape_index: usize },
+}
+
+#[derive(Clone, Copy)]
+enum FileAction {
+    OpenImage,
+    OpenProject,
+    SaveProject,
+    ExportSvg,
+    ExportDxf,
+    ExportCsv,
+    ExportJson,
+    ExportImage,
+    ImportDxf,
+    AddCalibrationImage,
+    OpenCameraProfile,
+    SaveCameraProfile,
+}
+
+const MODES: [(Mode, Action, &str, ImageSource); 4] = [
+    (Mode::DRAG, Action::DragMode, "Drag", include_image!("../assets/hand.png")),
+    (Mode::SELECTION, Action::SelectionMode, "Selection", include_image!("../assets/cursor.png")),
+    (Mode::LINE, Action::LineMode, "Line", include_image!("../assets/line.png")),
+    (Mode::CIRCLE, Action::CircleMode, "Circle", include_image!("../assets/circle.png")),
     //(Mode::SPLINE, "Spline"),
 ];
 
@@ -38,12 +122,71 @@ pub struct BlueKompassApp {
     image: Option<BlueKompassImage>,
     mode: Mode,
     opened_file: Option<PathBuf>,
-    open_file_dialog: Option<FileDialog>,
+    open_file_dialog: Option<(FileAction, FileDialog)>,
+    project_file: Option<PathBuf>,
+    /// Version the project is saved with, older while its shapes could not be moved to the current coordinates
+    project_version: u32,
+    error: Option<String>,
+    /// What went differently than asked without failing, shown until it is closed
+    notice: Option<String>,
     builder: Builder,
     shapes: Vec<Box<dyn Shape>>,
+    layers: Vec<Layer>,
+    active_layer: usize,
+    default_style: Style,
+    show_default_style: bool,
+    calibration: Calibration,
+    show_calibration: bool,
+    show_frames: bool,
+    frame_draft: frames::FrameDraft,
+    reference_length: f64,
+    show_labels: bool,
+    show_loupe: bool,
+    adjustments: Adjustments,
+    show_adjustments: bool,
+    image_operations: Vec<ImageOperation>,
+    show_deskew: bool,
+    /// Points of the reference line while they are picked
+    deskew_points: Option<Vec<Point>>,
+    /// Deskew by adding a datum frame rather than rotating the image
+    deskew_frame: bool,
+    rectification: Option<rectify::Rectification>,
+    show_lens: bool,
+    show_image_info: bool,
+    /// The calibration may still come from the resolution of the image just opened
+    resolution_pending: bool,
+    lens: lens::LensCalibration,
+    show_histogram: bool,
+    histogram_region: intensity::HistogramRegion,
+    histogram_cache: Option<(Region, Histogram)>,
+    show_profile: bool,
+    raster_options: RasterOptions,
+    show_export_image: bool,
+    keymap: Keymap,
+    show_keymap: bool,
+    capturing_action: Option<Action>,
     plot_bounds: PlotBounds,
+    /// Bounds of the plot at the last frame
+    view_bounds: PlotBounds,
+    /// Position of the pointer over the plot
+    cursor: Option<Point>,
+    /// Screen points per image pixel
+    zoom: f64,
     selected_shape_index: Option<usize>,
-    selected_point_index: Option<usize>,
+    dragging: Option<Dragging>,
+    invalid_preview: Option<Box<dyn Shape>>,
+    alignment: Option<reference::Alignment>,
+    show_comparison: bool,
+    /// Layer of the nominal shapes of the deviation analysis, chosen by the user when no reference was imported
+    comparison_layer: Option<usize>,
+    show_tolerance_report: bool,
+    report_only_failed: bool,
+    deviation_tolerance: DeviationTolerance,
+    pairing_distance: f64,
+    next_shape_id: usize,
+    outliner_filter: Option<ShapeKind>,
+    center_on: Option<PlotPoint>,
+    fit_view: bool,
 }
 
 impl Default for BlueKompassApp {
@@ -53,11 +196,92 @@ impl Default for BlueKompassApp {
             mode: Mode::DRAG,
             opened_file: None,
             open_file_dialog: None,
+            project_file: None,
+            project_version: VERSION,
+            error: None,
+            notice: None,
             builder: Builder::new(),
             shapes: Vec::default(),
+            layers: vec![Layer::default()],
+            active_layer: 0,
+            default_style: Style::default(),
+            show_default_style: false,
+            calibration: Calibration::default(),
+            show_calibration: false,
+            show_frames: false,
+            frame_draft: frames::FrameDraft::default(),
+            reference_length: 1.0,
+            show_labels: true,
+            show_loupe: false,
+            adjustments: Adjustments::default(),
+            show_adjustments: false,
+            image_operations: Vec::new(),
+            show_deskew: false,
+            deskew_points: None,
+            deskew_frame: false,
+            rectification: None,
+            show_lens: false,
+            show_image_info: false,
+            resolution_pending: false,
+            lens: lens::LensCalibration::default(),
+            show_histogram: false,
+            histogram_region: intensity::HistogramRegion::Image,
+            histogram_cache: None,
+            show_profile: false,
+            raster_options: RasterOptions::default(),
+            show_export_image: false,
+            keymap: Keymap::default(),
+            show_keymap: false,
+            capturing_action: None,
             plot_bounds: PlotBounds::from_min_max([0., 0.], [0., 0.]),
+            view_bounds: PlotBounds::from_min_max([0., 0.], [0., 0.]),
+            cursor: None,
+            zoom: 1.0,
             selected_shape_index: None,
-            selected_point_index: None,
+            dragging: None,
+            invalid_preview: None,
+            alignment: None,
+            show_comparison: false,
+            comparison_layer: None,
+            show_tolerance_report: false,
+            report_only_failed: true,
+            deviation_tolerance: DeviationTolerance::default(),
+            pairing_distance: DEFAULT_PAIRING_DISTANCE,
+            next_shape_id: 0,
+            outliner_filter: None,
+            center_on: None,
+            fit_view: false,
+        }
+    }
+}
+
+impl BlueKompassApp {
+    /// Application with the keymap of the user, loaded by the caller so that building the default one reads no file
+    pub fn new(options: StartupOptions, keymap: Keymap) -> Self {
+        let mut app = Self { keymap, ..Self::default() };
+        if let Some(file) = &options.file {
+            if let Err(error) = app.open_path(file) {
+                app.error = Some(format!("{}: {error}", file.display()));
+            }
+        }
+        if let Some(mode) = options.mode {
+            app.set_mode(mode);
+        }
+        app.fit_view = options.zoom_to_fit;
+        app
+    }
+
+    fn center_view(&mut self, plot_ui: &mut PlotUi) {
+        if std::mem::take(&mut self.fit_view) {
+            plot_ui.set_auto_bounds(Vec2b::TRUE);
+        }
+        if let Some(center) = self.center_on.take() {
+            let bounds = plot_ui.plot_bounds();
+            let [half_width, half_height] = [0.5 * bounds.width(), 0.5 * bounds.height()];
+            plot_ui.set_plot_bounds(PlotBounds::from_min_max(
+                [center.x - half_width, center.y - half_height],
+                [center.x + half_width, center.y + half_height],
+            ));
         }
     }
 }
@@ -67,34 +291,210 @@ impl eframe::App for BlueKompassApp {
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
                 ui.menu_button("File", |ui| {
-                    if ui.button("Open").clicked() {
+                    if ui.button("Open image").clicked() {
                         self.open_image();
+                        ui.close_menu();
+                    }
+                    if ui.button("Open project").clicked() {
+                        self.open_dialog(FileAction::OpenProject);
+                        ui.close_menu();
+                    }
+                    if ui.button("Save project").clicked() {
+                        self.save();
+                        ui.close_menu();
                     }
+                    if ui.button("Save project as").clicked() {
+                        self.open_dialog(FileAction::SaveProject);
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui.button("Export SVG").clicked() {
+                        self.open_dialog(FileAction::ExportSvg);
+                        ui.close_menu();
+                    }
+                    if ui.button("Export DXF").clicked() {
+                        self.open_dialog(FileAction::ExportDxf);
+                        ui.close_menu();
+                    }
+                    if ui.button("Import DXF reference").clicked() {
+                        self.open_dialog(FileAction::ImportDxf);
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui.button("Export image").clicked() {
+                        self.show_export_image = true;
+                        ui.close_menu();
+                    }
+                    if ui.button("Export measurements CSV").clicked() {
+                        self.open_dialog(FileAction::ExportCsv);
+                        ui.close_menu();
+                    }
+                    if ui.button("Export measurements JSON").clicked() {
+                        self.open_dialog(FileAction::ExportJson);
+                        ui.close_menu();
+                    }
+                    ui.separator();
                     if ui.button("Quit").clicked() {
                         std::process::exit(0);
                     }
                 });
+                ui.menu_button("View", |ui| {
+                    if ui.button("Zoom to fit").clicked() {
+                        self.fit_view = true;
+                        ui.close_menu();
+                    }
+                    ui.checkbox(&mut self.show_labels, "Labels");
+                    ui.checkbox(&mut self.show_loupe, "Loupe").on_hover_text("Magnify the cursor while placing points");
+                    if ui.button("Image adjustments").clicked() {
+                        self.show_adjustments = true;
+                        ui.close_menu();
+                    }
+                    if ui.button("Histogram").clicked() {
+                        self.show_histogram = true;
+                        ui.close_menu();
+                    }
+                    if ui.button("Intensity profile").clicked() {
+                        self.show_profile = true;
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui.button("Tolerance report").clicked() {
+                        self.show_tolerance_report = true;
+                        ui.close_menu();
+                    }
+                    if ui.button("Deviation analysis").clicked() {
+                        self.start_comparison();
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button("Image", |ui| {
+                    ui.add_enabled_ui(self.image.is_some(), |ui| {
+                        for (operation, text) in [
+                            (ImageOperation::RotateClockwise, "Rotate 90° clockwise"),
+                            (ImageOperation::RotateCounterclockwise, "Rotate 90° counterclockwise"),
+                            (ImageOperation::FlipHorizontal, "Flip horizontally"),
+                            (ImageOperation::FlipVertical, "Flip vertically"),
+                        ] {
+                            if ui.button(text).clicked() {
+                                self.transform_image(operation);
+                                ui.close_menu();
+                            }
+                        }
+                        ui.separator();
+                        if ui.button("Deskew").clicked() {
+                            self.show_deskew = true;
+                            ui.close_menu();
+                        }
+                        if ui.button("Perspective rectification").clicked() {
+                            self.rectification = Some(rectify::Rectification::new(&self.calibration.unit));
+                            ui.close_menu();
+                        }
+                        if ui.button("Lens distortion").clicked() {
+                            self.show_lens = true;
+                            ui.close_menu();
+                        }
+                        ui.separator();
+                        if ui.button("Image info").clicked() {
+                            self.show_image_info = true;
+                            ui.close_menu();
+                        }
+                        if ui.add_enabled(!self.image_operations.is_empty(), Button::new("Reset orientation")).clicked() {
+                            self.reset_orientation();
+                            ui.close_menu();
+                        }
+                    });
+                });
+                ui.menu_button("Edit", |ui| {
+                    if ui.button("Copy measurements").clicked() {
+                        let text = self.measurements_text();
+                        ui.ctx().output_mut(|output| output.copied_text = text);
+                        ui.close_menu();
+                    }
+                    if ui.button("Calibration").clicked() {
+                        self.show_calibration = true;
+                        ui.close_menu();
+                    }
+                    if ui.button("Datum frames").clicked() {
+                        self.show_frames = true;
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui.button("Default style").clicked() {
+                        self.show_default_style = true;
+                        ui.close_menu();
+                    }
+                    if ui.button("Keyboard shortcuts").clicked() {
+                        self.show_keymap = true;
+                        ui.close_menu();
+                    }
+                });
             });
         });
 
+        if self.image.is_some() {
+            egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| self.status_bar(ui));
+            egui::SidePanel::left("outliner_panel").show(ctx, |ui| {
+                self.layers_panel(ui);
+                ui.separator();
+                self.style_panel(ui);
+                self.coordinates_panel(ui);
+                self.tolerance_panel(ui);
+                self.deviation_tolerance_panel(ui);
+                self.outliner(ui);
+            });
+        }
+
         egui::CentralPanel::default().show(ctx, |ui| {
             // Define layout
             ui.with_layout(Layout::left_to_right(Layout::default().horizontal_align()), |ui|{
                 // Select mode with buttons
-                for (mode, button_text, image_path) in MODES {
+                for (mode, action, button_text, image_path) in MODES {
                     // ui.add(Button::image(Image::new(include_image!("../assets/bluekompass.png"))));
-                    if ui.add(Button::image_and_text(Image::new(image_path), button_text)).clicked() {
-                        self.mode = mode;
-                        self.builder.reset();
+                    let shortcut = self.keymap.key(action).map_or("", |key| key.name());
+                    let button = ui.add(Button::image_and_text(Image::new(image_path), button_text))
+                        .on_hover_text(shortcut);
+                    if button.clicked() {
+                        self.set_mode(mode);
                     }
                 }
             });
 
 
-            self.refresh_image(ctx);
+            self.refresh_file_dialog(ctx);
+            self.show_error(ctx);
+            self.show_notice(ctx);
+            self.export_image_window(ctx);
+            self.alignment_window(ctx);
+            self.comparison_window(ctx);
+            self.tolerance_report_window(ctx);
+            self.adjustments_window(ctx);
+            self.histogram_window(ctx);
+            self.profile_window(ctx);
+            self.default_style_window(ctx);
+            self.calibration_window(ctx);
+            self.frames_window(ctx);
+            self.deskew_window(ctx);
+            self.rectification_window(ctx);
+            self.lens_window(ctx);
+            self.image_info_window(ctx);
+            self.default_calibration();
+            self.handle_shortcuts(ctx);
+            self.keymap_window(ctx);
 
-            if let Some(image) = &mut self.image {
-                let (image_id, size) = image.load(ui);
+            let loaded = self.image.as_mut().map(|image| {
+                image.load(ui, &self.adjustments, &self.image_operations)
+                    .map_err(|error| format!("{}: {error}", image.path().display()))
+            });
+            let loaded = match loaded {
+                Some(Ok(loaded)) => Some(loaded),
+                Some(Err(error)) => {
+                    self.error = Some(error);
+                    self.image = None;
+                    None
+                }
+                None => None,
+            };
+            if let Some((image_id, size)) = loaded {
 
                 let plot = Plot::new("BlueKompass Plot")
                     .data_aspect(1.0)
@@ -107,18 +507,32 @@ impl eframe::App for BlueKompassApp {
                 plot.show(ui, |plot_ui| {
                     self.draw_image(plot_ui, image_id, size);
 
-                    match self.mode {
-                        Mode::DRAG => self.unselect_shape(),
-                        Mode::SELECTION => self.select(plot_ui),
-                        Mode::LINE => self.build(plot_ui, Line),
-                        Mode::CIRCLE => self.build(plot_ui, Circle),
+                    if !self.pick_alignment_point(plot_ui)
+                        && !self.pick_deskew_point(plot_ui)
+                        && !self.pick_rectification_point(plot_ui)
+                    {
+                        match self.mode {
+                            Mode::DRAG => self.unselect_shape(),
+                            Mode::SELECTION => self.select(plot_ui),
+                            Mode::LINE => self.build(plot_ui, Line),
+                            Mode::CIRCLE => self.build(plot_ui, Circle),
+                        }
                     }
 
+                    self.center_view(plot_ui);
+                    self.view_bounds = plot_ui.plot_bounds();
+                    self.track_cursor(plot_ui);
                     self.draw(plot_ui);
+                    self.draw_frame(plot_ui);
+                    self.draw_comparison(plot_ui);
+                    self.draw_alignment(plot_ui);
+                    self.draw_deskew(plot_ui);
+                    self.draw_rectification(plot_ui);
                 });
+                self.loupe(ui.ctx(), image_id, size);
             } else {
                 ui.with_layout(Layout::centered_and_justified(egui::Direction::TopDown), |ui| {
-                    ui.label("Welcome to BlueKompass v0.1.0 !\nStart by opening an image from the \"Open\" menu.");
+                    ui.label("Welcome to BlueKompass v0.1.0 !\nStart by opening an image from the \"Open image\" menu.");
                 });
             }
         });
@@ -0,0 +1,37 @@
+use super::BlueKompassApp;
+
+use eframe::egui;
+use egui::{Context, Slider};
+
+use bluekompass_core::adjustments::Adjustments;
+
+impl BlueKompassApp {
+    pub fn adjustments_window(&mut self, ctx: &Context) {
+        egui::Window::new("Image adjustments")
+            .open(&mut self.show_adjustments)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let adjustments = &mut self.adjustments;
+                egui::Grid::new("adjustments_grid").num_columns(2).show(ui, |ui| {
+                    ui.label("Brightness");
+                    ui.add(Slider::new(&mut adjustments.brightness, -1.0..=1.0));
+                    ui.end_row();
+
+                    ui.label("Contrast");
+                    ui.add(Slider::new(&mut adjustments.contrast, 0.0..=4.0));
+                    ui.end_row();
+
+                    ui.label("Gamma");
+                    ui.add(Slider::new(&mut adjustments.gamma, 0.1..=5.0).logarithmic(true));
+                    ui.end_row();
+                });
+                ui.checkbox(&mut adjustments.auto_levels, "Auto-levels");
+                ui.checkbox(&mut adjustments.grayscale, "Grayscale");
+                ui.checkbox(&mut adjustments.invert, "Invert");
+                ui.weak("Adjustments only change the display, measurements use the original image.");
+                if ui.button("Reset").clicked() {
+                    *adjustments = Adjustments::default();
+                }
+            });
+    }
+}
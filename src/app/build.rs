@@ -2,8 +2,26 @@ use super::BlueKompassApp;
 use egui_plot::PlotUi;
 
 use crate::builders::ShapeBuilder;
+use bluekompass_core::shapes::Shape;
+use bluekompass_core::style::Style;
+use crate::render::to_point;
 
 impl BlueKompassApp {
+    pub fn add_shape(&mut self, shape: Box<dyn Shape>) {
+        self.add_shape_on_layer(shape, self.active_layer, self.default_style.clone());
+    }
+
+    pub fn add_shape_on_layer(&mut self, mut shape: Box<dyn Shape>, layer: usize, style: Style) {
+        self.next_shape_id += 1;
+        let name = format!("{} {}", shape.kind().name(), self.next_shape_id);
+        let properties = shape.properties_mut();
+        properties.id = self.next_shape_id;
+        properties.layer = layer;
+        properties.style = style;
+        properties.name = name;
+        self.shapes.push(shape);
+    }
+
     pub fn build<T: ShapeBuilder>(&mut self, plot_ui: &mut PlotUi, shape_builder: T) {
         // Fix plot bounds and unselect current shape
         self.plot_bounds = plot_ui.plot_bounds();
@@ -14,16 +32,21 @@ impl BlueKompassApp {
         if plot_ui.ctx().input(|i| i.pointer.primary_clicked()) {
             if response.contains_pointer() {
                 if let Some(pos) = plot_ui.pointer_coordinate() {
-                    self.builder.set_next_point(pos);
-                    if let Some(shape) = self.builder.build(shape_builder) {
-                        self.shapes.push(shape);
-                        self.builder.reset();
-                    } 
+                    self.builder.set_next_point(to_point(pos));
+                    match self.builder.build(shape_builder) {
+                        Some(shape) if shape.is_valid() => {
+                            self.add_shape(shape);
+                            self.builder.reset();
+                        }
+                        // Refuse the point that makes the shape degenerate
+                        Some(_) => self.builder.undo_last_point(),
+                        None => (),
+                    }
                 }
             }
         } else if let Some(pos) = plot_ui.pointer_coordinate() {
             if response.contains_pointer() {
-                self.builder.draw(plot_ui, pos, shape_builder);
+                self.builder.draw(plot_ui, to_point(pos), &self.layers[self.active_layer], &self.default_style, shape_builder);
             }
         }
 
@@ -0,0 +1,101 @@
+use super::BlueKompassApp;
+
+use eframe::egui;
+use egui::{ComboBox, Context, DragValue, TextEdit};
+
+use bluekompass_core::calibration::Calibration;
+use bluekompass_core::coordinates::YAxis;
+use bluekompass_core::shapes::Geometry;
+
+impl BlueKompassApp {
+    /// Length in pixels of the selected line, if any
+    fn selected_line_length(&self) -> Option<f64> {
+        let shape = &self.shapes[self.selected_shape_index?];
+        match shape.geometry()? {
+            Geometry::Line([a, b]) => Some((b.x - a.x).hypot(b.y - a.y)),
+            Geometry::Circle { .. } => None,
+        }
+    }
+
+    pub fn calibration_window(&mut self, ctx: &Context) {
+        let line_length = self.selected_line_length();
+        let resolution = self.image_resolution().and_then(|resolution| Some((resolution.dpi()?, resolution.calibration())));
+        egui::Window::new("Calibration")
+            .open(&mut self.show_calibration)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let calibration = &mut self.calibration;
+                egui::Grid::new("calibration_grid").num_columns(2).show(ui, |ui| {
+                    ui.label("Scale");
+                    ui.add(
+                        DragValue::new(&mut calibration.scale)
+                            .clamp_range(1e-9..=f64::MAX)
+                            .speed(1e-3)
+                            .suffix(format!(" {}/px", calibration.unit))
+                    );
+                    ui.end_row();
+
+                    ui.label("Unit");
+                    ui.add(TextEdit::singleline(&mut calibration.unit).desired_width(60.));
+                    ui.end_row();
+
+                    ui.label("Y axis");
+                    ComboBox::from_id_source("y_axis")
+                        .selected_text(calibration.y_axis.name())
+                        .show_ui(ui, |ui| {
+                            for y_axis in YAxis::ALL {
+                                ui.selectable_value(&mut calibration.y_axis, y_axis, y_axis.name());
+                            }
+                        })
+                        .response
+                        .on_hover_text("Orientation of read out and exported coordinates, from the top left corner of the image");
+                    ui.end_row();
+                });
+                ui.separator();
+
+                match line_length {
+                    Some(pixels) => {
+                        ui.label(format!("Selected line: {pixels:.2} px"));
+                        ui.horizontal(|ui| {
+                            ui.label("Known length");
+                            ui.add(DragValue::new(&mut self.reference_length).clamp_range(0.0..=f64::MAX).speed(0.1));
+                            ui.label(&calibration.unit);
+                            if ui.button("Calibrate").clicked() {
+                                let unit = calibration.unit.clone();
+                                if let Some(reference) = Calibration::from_reference(pixels, self.reference_length, unit) {
+                                    calibration.scale = reference.scale;
+                                }
+                            }
+                        });
+                    }
+                    None => {
+                        ui.weak("Select a line of known length to calibrate from it");
+                    }
+                }
+
+                if let Some(([x_dpi, y_dpi], reference)) = &resolution {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("Image resolution: {x_dpi:.1} × {y_dpi:.1} dpi"));
+                        match reference {
+                            Some(reference) => {
+                                if ui.button("Use").on_hover_text(format!("{:.5} mm/px", reference.scale)).clicked() {
+                                    calibration.scale = reference.scale;
+                                    calibration.unit = reference.unit.clone();
+                                }
+                            }
+                            None => {
+                                ui.weak("Pixels not square");
+                            }
+                        }
+                    });
+                }
+
+                if ui.button("Reset").clicked() {
+                    // Coordinate settings are kept
+                    let default = Calibration::default();
+                    calibration.scale = default.scale;
+                    calibration.unit = default.unit;
+                }
+            });
+    }
+}
@@ -0,0 +1,229 @@
+use super::BlueKompassApp;
+
+use std::fmt::Write;
+
+use eframe::egui;
+use egui::{ComboBox, Context, DragValue, RichText, Ui};
+use egui_plot::{LineStyle, PlotPoints, PlotUi};
+
+use bluekompass_core::color::Color;
+use bluekompass_core::deviation::{compare, Comparison, DeviationTolerance};
+use bluekompass_core::shapes::{build_shape, Shape};
+use bluekompass_core::style::Style;
+use crate::render::{to_plot_point, Draw, GREEN, RED};
+
+fn tolerance_editor(ui: &mut Ui, tolerance: &mut DeviationTolerance, unit: &str) {
+    egui::Grid::new(ui.next_auto_id()).num_columns(2).show(ui, |ui| {
+        ui.label("Position");
+        ui.add(
+            DragValue::new(&mut tolerance.position)
+                .clamp_range(0.0..=f64::MAX)
+                .speed(0.01)
+                .prefix("± ")
+                .suffix(format!(" {unit}"))
+        );
+        ui.end_row();
+
+        ui.label("Radius");
+        ui.add(
+            DragValue::new(&mut tolerance.radius)
+                .clamp_range(0.0..=f64::MAX)
+                .speed(0.01)
+                .prefix("± ")
+                .suffix(format!(" {unit}"))
+        );
+        ui.end_row();
+
+        ui.label("Angle");
+        ui.add(DragValue::new(&mut tolerance.angle).clamp_range(0.0..=90.).speed(0.01).prefix("± ").suffix("°"));
+        ui.end_row();
+    });
+}
+
+fn optional(value: Option<f64>) -> String {
+    value.map_or_else(String::new, |value| format!("{value:.3}"))
+}
+
+impl BlueKompassApp {
+    /// Start comparing the traced shapes with the last reference layer imported, the user chooses the nominal layer
+    /// when there is none
+    pub fn start_comparison(&mut self) {
+        self.show_comparison = true;
+        self.comparison_layer = self.layers.iter().rposition(|layer| layer.reference);
+    }
+
+    /// Compare visible shapes of the other layers with the shapes of the reference layer
+    fn comparison(&self) -> Option<Comparison> {
+        let layer = self.comparison_layer?;
+        let (nominal, traced): (Vec<&dyn Shape>, Vec<&dyn Shape>) = self.shapes.iter()
+            .enumerate()
+            .filter(|&(index, shape)| shape.properties().layer == layer || self.is_visible(index))
+            .map(|(_, shape)| shape.as_ref())
+            .partition(|shape| shape.properties().layer == layer);
+        Some(compare(&traced, &nominal, self.deviation_tolerance, self.pairing_distance, &self.calibration))
+    }
+
+    /// Draw traced shapes in green or red depending on their deviation, linked to their nominal shape
+    pub fn draw_comparison(&self, plot_ui: &mut PlotUi) {
+        let Some(comparison) = self.comparison() else {
+            return;
+        };
+        let find = |id: usize| self.shapes.iter().find(|shape| shape.properties().id == id);
+        for deviation in &comparison.deviations {
+            let (Some(traced), Some(nominal)) = (find(deviation.traced_id), find(deviation.nominal_id)) else {
+                continue;
+            };
+            let color = if deviation.passed() { GREEN } else { RED };
+            let layer = &self.layers[traced.properties().layer];
+            if let Some(mut highlight) = build_shape(traced.kind(), traced.as_slice()) {
+                let style = &traced.properties().style;
+                highlight.properties_mut().style = Style {
+                    color: Some(Color(color.to_srgba_unmultiplied())),
+                    width: Some(style.width(layer) + 2.),
+                    show_grips: false,
+                    ..style.clone()
+                };
+                highlight.draw(plot_ui, layer);
+            }
+            plot_ui.line(
+                egui_plot::Line::new(PlotPoints::Owned(vec![
+                    to_plot_point(traced.center()),
+                    to_plot_point(nominal.center()),
+                ]))
+                .color(color)
+                .style(LineStyle::dashed_dense())
+            );
+        }
+    }
+
+    /// Tolerance of the selected shape when it is a nominal one
+    pub fn deviation_tolerance_panel(&mut self, ui: &mut Ui) {
+        let (Some(layer), Some(selection_index)) = (self.comparison_layer, self.selected_shape_index) else {
+            return;
+        };
+        let default_tolerance = self.deviation_tolerance;
+        let properties = self.shapes[selection_index].properties_mut();
+        if properties.layer != layer {
+            return;
+        }
+        ui.heading(format!("Deviation tolerance of {}", properties.name));
+        let mut custom = properties.deviation_tolerance.is_some();
+        if ui.checkbox(&mut custom, "Own tolerance").changed() {
+            properties.deviation_tolerance = custom.then_some(default_tolerance);
+        }
+        if let Some(tolerance) = &mut properties.deviation_tolerance {
+            tolerance_editor(ui, tolerance, &self.calibration.unit);
+        }
+        ui.separator();
+    }
+
+    /// Names of the shapes with the ids, in the order of the ids
+    fn shape_names(&self, ids: &[usize]) -> Vec<&str> {
+        ids.iter()
+            .filter_map(|&id| self.shapes.iter().find(|shape| shape.properties().id == id))
+            .map(|shape| shape.properties().name.as_str())
+            .collect()
+    }
+
+    pub fn comparison_window(&mut self, ctx: &Context) {
+        if !self.show_comparison {
+            return;
+        }
+        let comparison = self.comparison();
+        let unit = self.calibration.unit.clone();
+        let mut open = true;
+        egui::Window::new("Deviation analysis")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let layer = &mut self.comparison_layer;
+                ComboBox::from_label("Nominal layer")
+                    .selected_text(layer.map_or("Choose a layer", |layer| self.layers[layer].name.as_str()))
+                    .show_ui(ui, |ui| {
+                        for (index, candidate) in self.layers.iter().enumerate() {
+                            ui.selectable_value(layer, Some(index), &candidate.name);
+                        }
+                    });
+                ui.collapsing("Default tolerance", |ui| {
+                    tolerance_editor(ui, &mut self.deviation_tolerance, &unit);
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Pairing distance");
+                    ui.add(
+                        DragValue::new(&mut self.pairing_distance)
+                            .clamp_range(0.0..=f64::MAX)
+                            .speed(0.1)
+                            .suffix(format!(" {unit}"))
+                    ).on_hover_text("Largest distance between the centers of a traced shape and its nominal shape");
+                });
+                ui.separator();
+
+                let Some(comparison) = &comparison else {
+                    ui.weak("Choose the layer of the nominal shapes, a DXF import gives a reference layer.");
+                    return;
+                };
+
+                egui::ScrollArea::vertical().max_height(300.).show(ui, |ui| {
+                    egui::Grid::new("deviation_table").striped(true).num_columns(6).show(ui, |ui| {
+                        for header in ["Traced", "Nominal", "Position", "Radius", "Angle", "Status"] {
+                            ui.strong(header);
+                        }
+                        ui.end_row();
+                        for deviation in &comparison.deviations {
+                            ui.label(&deviation.traced_name);
+                            ui.label(&deviation.nominal_name);
+                            ui.label(format!("{:.3} {unit}", deviation.position));
+                            ui.label(deviation.radius.map_or_else(String::new, |radius| format!("{radius:+.3} {unit}")));
+                            ui.label(deviation.angle.map_or_else(String::new, |angle| format!("{angle:+.3}°")));
+                            if deviation.passed() {
+                                ui.label(RichText::new("PASS").color(GREEN));
+                            } else {
+                                ui.label(RichText::new("FAIL").color(RED));
+                            }
+                            ui.end_row();
+                        }
+                    });
+                });
+                ui.separator();
+
+                ui.label(format!(
+                    "{} paired, {} out of tolerance, {} traced without nominal, {} nominal not traced",
+                    comparison.deviations.len(),
+                    comparison.failed(),
+                    comparison.unpaired_traced.len(),
+                    comparison.unpaired_nominal.len(),
+                ));
+                for (title, ids) in [
+                    ("Traced without nominal", &comparison.unpaired_traced),
+                    ("Nominal not traced", &comparison.unpaired_nominal),
+                ] {
+                    if !ids.is_empty() {
+                        ui.collapsing(title, |ui| {
+                            for name in self.shape_names(ids) {
+                                ui.label(name);
+                            }
+                        });
+                    }
+                }
+                if ui.button("Copy table").clicked() {
+                    let mut text = String::from("traced\tnominal\tposition\tradius\tangle\tunit\tstatus\n");
+                    for deviation in &comparison.deviations {
+                        let _ = writeln!(
+                            text,
+                            "{}\t{}\t{:.3}\t{}\t{}\t{unit}\t{}",
+                            deviation.traced_name,
+                            deviation.nominal_name,
+                            deviation.position,
+                            optional(deviation.radius),
+                            optional(deviation.angle),
+                            if deviation.passed() { "PASS" } else { "FAIL" },
+                        );
+                    }
+                    ui.ctx().output_mut(|output| output.copied_text = text);
+                }
+            });
+        if !open {
+            self.show_comparison = false;
+            self.comparison_layer = None;
+        }
+    }
+}
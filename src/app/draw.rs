@@ -2,10 +2,23 @@ use super::BlueKompassApp;
 
 use egui_plot::PlotUi;
 
+use bluekompass_core::measure::label;
+use crate::render::{draw_label, label_color, Draw};
+
 impl BlueKompassApp {
     pub fn draw(&mut self, plot_ui: &mut PlotUi) {
-        for shape in &self.shapes {
-            shape.draw(plot_ui);
+        for (index, shape) in self.shapes.iter().enumerate() {
+            if self.is_visible(index) {
+                let layer = &self.layers[shape.properties().layer];
+                shape.draw(plot_ui, layer);
+                if self.show_labels && shape.is_valid() {
+                    let color = label_color(shape.as_ref(), layer, &self.calibration);
+                    draw_label(plot_ui, shape.as_ref(), color, label(shape.as_ref(), &self.calibration));
+                }
+            }
+        }
+        if let Some(shape) = self.invalid_preview.take() {
+            shape.draw(plot_ui, &self.layers[shape.properties().layer]);
         }
     }
 }
@@ -0,0 +1,179 @@
+use super::{BlueKompassApp, FileAction};
+
+use eframe::egui;
+use egui::{Button, Context, DragValue};
+use std::ffi::OsStr;
+use std::path::Path;
+use egui_file::FileDialog;
+use image::ImageFormat;
+
+impl FileAction {
+    fn extension(&self) -> &'static str {
+        match self {
+            FileAction::OpenImage => "png",
+            FileAction::OpenProject | FileAction::SaveProject => "bkp",
+            FileAction::ExportSvg => "svg",
+            FileAction::ExportDxf => "dxf",
+            FileAction::ExportCsv => "csv",
+            FileAction::ExportJson => "json",
+            FileAction::ExportImage => "png",
+            FileAction::ImportDxf => "dxf",
+            FileAction::AddCalibrationImage => "png",
+            FileAction::OpenCameraProfile | FileAction::SaveCameraProfile => "bkc",
+        }
+    }
+
+    /// Whether the dialog of the action shows a file, images are shown in any format the loader decodes
+    fn accepts(&self, path: &Path) -> bool {
+        let Some(extension) = path.extension() else {
+            return false;
+        };
+        match self {
+            FileAction::OpenImage | FileAction::AddCalibrationImage => {
+                ImageFormat::from_extension(extension).is_some_and(|format| format.reading_enabled())
+            }
+            _ => extension == OsStr::new(self.extension()),
+        }
+    }
+}
+
+impl BlueKompassApp {
+    pub(super) fn open_dialog(&mut self, action: FileAction) {
+        // Show only the files the action can use
+        let filter = Box::new(move |path: &Path| -> bool { action.accepts(path) });
+        let mut dialog = match action {
+            FileAction::OpenImage
+            | FileAction::OpenProject
+            | FileAction::ImportDxf
+            | FileAction::AddCalibrationImage
+            | FileAction::OpenCameraProfile => {
+                FileDialog::open_file(self.opened_file.clone())
+            }
+            FileAction::SaveProject
+            | FileAction::ExportSvg
+            | FileAction::ExportDxf
+            | FileAction::ExportCsv
+            | FileAction::ExportJson
+            | FileAction::ExportImage
+            | FileAction::SaveCameraProfile => {
+                let default_name = self.opened_file.as_deref()
+                    .and_then(Path::file_stem)
+                    .map_or("untitled".into(), |stem| stem.to_string_lossy().into_owned());
+                FileDialog::save_file(self.opened_file.clone())
+                    .default_filename(format!("{default_name}.{}", action.extension()))
+            }
+        }.show_files_filter(filter);
+        dialog.open();
+        self.open_file_dialog = Some((action, dialog));
+    }
+
+    pub fn refresh_file_dialog(&mut self, ctx: &Context) {
+        let Some((action, dialog)) = &mut self.open_file_dialog else {
+            return;
+        };
+        if !dialog.show(ctx).selected() {
+            return;
+        }
+        let Some(file) = dialog.path().map(Path::to_path_buf) else {
+            return;
+        };
+        let is_jpeg = file.extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("jpg") || extension.eq_ignore_ascii_case("jpeg"));
+        let file = match action {
+            FileAction::OpenImage
+            | FileAction::OpenProject
+            | FileAction::ImportDxf
+            | FileAction::AddCalibrationImage
+            | FileAction::OpenCameraProfile => file,
+            FileAction::ExportImage if is_jpeg => file,
+            _ => file.with_extension(action.extension()),
+        };
+
+        let result = match action {
+            FileAction::OpenImage => self.load_chosen_image(&file),
+            FileAction::OpenProject => self.load_project(&file),
+            FileAction::SaveProject => self.save_project(&file),
+            FileAction::ExportSvg => self.export_svg(&file),
+            FileAction::ExportDxf => self.export_dxf(&file),
+            FileAction::ExportCsv => self.export_csv(&file),
+            FileAction::ExportJson => self.export_json(&file),
+            FileAction::ExportImage => self.export_image(&file),
+            FileAction::ImportDxf => self.import_dxf(&file),
+            FileAction::AddCalibrationImage => self.add_calibration_image(&file),
+            FileAction::OpenCameraProfile => self.load_camera_profile(&file),
+            FileAction::SaveCameraProfile => self.save_camera_profile(&file),
+        };
+        if let Err(error) = result {
+            self.error = Some(format!("{}: {error}", file.display()));
+        }
+        self.open_file_dialog = None;
+    }
+
+    pub fn export_image_window(&mut self, ctx: &Context) {
+        let mut export = false;
+        egui::Window::new("Export image")
+            .open(&mut self.show_export_image)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let size = self.image.as_ref().and_then(|image| image.size());
+                egui::Grid::new("export_image_grid").num_columns(2).show(ui, |ui| {
+                    ui.label("Scale");
+                    ui.add(DragValue::new(&mut self.raster_options.scale).clamp_range(0.05..=8.).speed(0.01));
+                    ui.end_row();
+
+                    ui.label("Size");
+                    match size {
+                        Some([width, height]) => {
+                            let scale = self.raster_options.scale;
+                            ui.label(format!("{:.0} × {:.0} px", width as f32 * scale, height as f32 * scale))
+                        }
+                        None => ui.weak("No image"),
+                    };
+                    ui.end_row();
+
+                    ui.label("Labels");
+                    ui.checkbox(&mut self.raster_options.labels, "Visible");
+                    ui.end_row();
+                });
+                export = ui.add_enabled(size.is_some(), Button::new("Export as PNG or JPEG")).clicked();
+            });
+        if export {
+            self.show_export_image = false;
+            self.open_dialog(FileAction::ExportImage);
+        }
+    }
+
+    pub fn show_error(&mut self, ctx: &Context) {
+        let Some(error) = &self.error else {
+            return;
+        };
+        let mut open = true;
+        egui::Window::new("Error")
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(error);
+            });
+        if !open {
+            self.error = None;
+        }
+    }
+
+    pub fn show_notice(&mut self, ctx: &Context) {
+        let Some(notice) = &self.notice else {
+            return;
+        };
+        let mut open = true;
+        egui::Window::new("Notice")
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(notice);
+            });
+        if !open {
+            self.notice = None;
+        }
+    }
+}
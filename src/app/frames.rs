@@ -0,0 +1,224 @@
+use super::BlueKompassApp;
+
+use eframe::egui;
+use egui::{Align2, ComboBox, Context, DragValue, RichText, TextEdit, Ui};
+use egui_plot::{PlotPoints, PlotUi, Text};
+
+use bluekompass_core::coordinates::Frame;
+use bluekompass_core::point::Point;
+use bluekompass_core::shapes::{Geometry, ShapeKind};
+use crate::render::{to_plot_point, GREEN, RED};
+
+/// Length of the axes of the active frame, in screen points
+const AXIS_LENGTH: f64 = 60.;
+
+/// Frame being defined, features are identified by the id of their shape
+#[derive(Default)]
+pub struct FrameDraft {
+    name: String,
+    /// Shape and index of its point, its center without index
+    origin: Option<(usize, Option<usize>)>,
+    /// Line along the X axis
+    axis: Option<usize>,
+}
+
+impl BlueKompassApp {
+    /// Points a frame can start from, with their shape id, point index and name
+    fn origin_candidates(&self) -> Vec<((usize, Option<usize>), String)> {
+        self.shapes.iter()
+            .filter(|shape| shape.is_valid())
+            .flat_map(|shape| {
+                let (id, name) = (shape.properties().id, &shape.properties().name);
+                match shape.kind() {
+                    ShapeKind::Line => vec![
+                        ((id, Some(0)), format!("{name} start")),
+                        ((id, Some(1)), format!("{name} end")),
+                        ((id, None), format!("{name} midpoint")),
+                    ],
+                    ShapeKind::Circle => vec![((id, None), format!("{name} center"))],
+                }
+            })
+            .collect()
+    }
+
+    fn feature_point(&self, (id, index): (usize, Option<usize>)) -> Option<Point> {
+        let shape = self.shapes.iter().find(|shape| shape.properties().id == id)?;
+        match index {
+            Some(index) => shape.as_slice().get(index).copied(),
+            None => Some(shape.center()),
+        }
+    }
+
+    fn axis_line(&self, id: usize) -> Option<[Point; 2]> {
+        let shape = self.shapes.iter().find(|shape| shape.properties().id == id)?;
+        match shape.geometry()? {
+            Geometry::Line(points) => Some(points),
+            Geometry::Circle { .. } => None,
+        }
+    }
+
+    fn draft_frame(&self) -> Option<Frame> {
+        let draft = &self.frame_draft;
+        let origin = self.feature_point(draft.origin?)?;
+        let name = if draft.name.trim().is_empty() {
+            format!("Frame {}", self.calibration.frames.len() + 1)
+        } else {
+            draft.name.trim().to_string()
+        };
+        Frame::new(name, origin, self.axis_line(draft.axis?)?)
+    }
+
+    pub fn frames_window(&mut self, ctx: &Context) {
+        if !self.show_frames {
+            return;
+        }
+        let origins = self.origin_candidates();
+        let lines: Vec<(usize, String)> = self.shapes.iter()
+            .filter(|shape| shape.kind() == ShapeKind::Line && shape.is_valid())
+            .map(|shape| (shape.properties().id, shape.properties().name.clone()))
+            .collect();
+        let frame = self.draft_frame();
+
+        let mut open = self.show_frames;
+        egui::Window::new("Datum frames")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let calibration = &mut self.calibration;
+                ui.radio_value(&mut calibration.active_frame, None, "Image");
+                let mut removed = None;
+                for (index, frame) in calibration.frames.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.radio_value(&mut calibration.active_frame, Some(index), "");
+                        ui.add(TextEdit::singleline(&mut frame.name).desired_width(120.));
+                        if ui.button("🗑").on_hover_text("Remove").clicked() {
+                            removed = Some(index);
+                        }
+                    });
+                }
+                if let Some(index) = removed {
+                    calibration.frames.remove(index);
+                    calibration.active_frame = match calibration.active_frame {
+                        Some(active) if active == index => None,
+                        Some(active) if active > index => Some(active - 1),
+                        active => active,
+                    };
+                }
+                ui.weak(format!("Y axis {}, set in the calibration", calibration.y_axis.name()));
+                ui.separator();
+
+                ui.strong("New frame");
+                let draft = &mut self.frame_draft;
+                egui::Grid::new("frame_grid").num_columns(2).show(ui, |ui| {
+                    ui.label("Name");
+                    ui.add(TextEdit::singleline(&mut draft.name).hint_text(format!("Frame {}", calibration.frames.len() + 1)));
+                    ui.end_row();
+
+                    ui.label("Origin");
+                    let selected = origins.iter()
+                        .find(|(origin, _)| Some(*origin) == draft.origin)
+                        .map_or("", |(_, name)| name.as_str());
+                    ComboBox::from_id_source("frame_origin")
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            for (origin, name) in &origins {
+                                ui.selectable_value(&mut draft.origin, Some(*origin), name);
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label("X axis");
+                    let selected = lines.iter()
+                        .find(|(id, _)| Some(*id) == draft.axis)
+                        .map_or("", |(_, name)| name.as_str());
+                    ComboBox::from_id_source("frame_axis")
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            for (id, name) in &lines {
+                                ui.selectable_value(&mut draft.axis, Some(*id), name);
+                            }
+                        });
+                    ui.end_row();
+                });
+                if lines.is_empty() {
+                    ui.weak("Draw a line along the X axis of the part first");
+                }
+                if ui.add_enabled(frame.is_some(), egui::Button::new("Add frame")).clicked() {
+                    if let Some(frame) = frame {
+                        calibration.frames.push(frame);
+                        calibration.active_frame = Some(calibration.frames.len() - 1);
+                        *draft = FrameDraft::default();
+                    }
+                }
+            });
+        self.show_frames = open;
+    }
+
+    /// Switch between the image and the datum frames
+    pub fn frame_selector(&mut self, ui: &mut Ui) {
+        let calibration = &mut self.calibration;
+        if calibration.frames.is_empty() {
+            return;
+        }
+        let selected = calibration.frame().map_or("Image", |frame| frame.name.as_str()).to_string();
+        ComboBox::from_id_source("active_frame")
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut calibration.active_frame, None, "Image");
+                for (index, frame) in calibration.frames.iter().enumerate() {
+                    ui.selectable_value(&mut calibration.active_frame, Some(index), &frame.name);
+                }
+            });
+    }
+
+    /// Defining points of the selected shape in the active frame, they can be typed in
+    pub fn coordinates_panel(&mut self, ui: &mut Ui) {
+        let Some(selection_index) = self.selected_shape_index else {
+            return;
+        };
+        let locked = self.is_locked(selection_index);
+        let calibration = &self.calibration;
+        let shape = &mut self.shapes[selection_index];
+        ui.heading("Coordinates");
+        egui::Grid::new("coordinates_grid").num_columns(3).show(ui, |ui| {
+            for index in 0..shape.as_slice().len() {
+                let mut coordinates = calibration.point(shape.as_slice()[index]);
+                ui.label(format!("Point {}", index + 1));
+                let x = ui.add_enabled(!locked, DragValue::new(&mut coordinates.x).speed(0.1).prefix("x "));
+                let y = ui.add_enabled(!locked, DragValue::new(&mut coordinates.y).speed(0.1).prefix("y "));
+                if x.changed() || y.changed() {
+                    // Refused when it makes the shape degenerate
+                    shape.try_replace(index, calibration.plot_point(coordinates));
+                }
+                ui.end_row();
+            }
+        });
+        let frame = calibration.frame().map_or("image", |frame| frame.name.as_str());
+        ui.weak(format!("In {} of the {frame}", calibration.unit));
+        ui.separator();
+    }
+
+    /// Axes of the active frame
+    pub fn draw_frame(&self, plot_ui: &mut PlotUi) {
+        let Some(frame) = self.calibration.frame() else {
+            return;
+        };
+        let length = AXIS_LENGTH / plot_ui.transform().dpos_dvalue_x();
+        let y_axis = self.calibration.y_axis;
+        for (end, color, name) in [
+            (Point::new(length, 0.), RED, "X"),
+            (y_axis.apply(Point::new(0., length)), GREEN, "Y"),
+        ] {
+            let end = frame.from_frame(end);
+            plot_ui.line(
+                egui_plot::Line::new(PlotPoints::Owned(vec![to_plot_point(frame.origin), to_plot_point(end)]))
+                    .color(color)
+                    .width(2.)
+            );
+            plot_ui.text(
+                Text::new(to_plot_point(end), RichText::new(name).color(color).strong())
+                    .anchor(Align2::LEFT_BOTTOM)
+            );
+        }
+    }
+}
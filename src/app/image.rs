@@ -1,41 +1,52 @@
-use super::{BlueKompassApp, Mode, BlueKompassImage};
+use super::{BlueKompassApp, FileAction, Mode, BlueKompassImage};
 
 use eframe::egui;
 use egui_plot::{PlotImage, PlotPoint, PlotUi};
-use egui::{Context, TextureId, Vec2};
-use std::ffi::OsStr;
+use egui::{TextureId, Vec2};
+use std::io;
 use std::path::Path;
-use egui_file::FileDialog;
 
 impl BlueKompassApp {
     pub fn open_image(&mut self) {
-        // Show only files with the extension "png".
-        let filter = Box::new({
-            let ext = Some(OsStr::new("png"));
-            move |path: &Path| -> bool { path.extension() == ext }
-        });
-        let mut dialog = FileDialog::open_file(self.opened_file.clone()).show_files_filter(filter);
-        dialog.open();
-        self.open_file_dialog = Some(dialog);
+        self.open_dialog(FileAction::OpenImage);
         self.mode = Mode::SELECTION;
     }
 
-    pub fn refresh_image(&mut self, ctx: &Context) {
-        if let Some(dialog) = &mut self.open_file_dialog {
-            if dialog.show(ctx).selected() {
-                if let Some(file) = dialog.path() {
-                    self.opened_file = Some(file.to_path_buf());
-                    self.image = Some(BlueKompassImage::new(file.to_path_buf()))
-                }
-            }
+    /// Open a project or an image depending on the extension of the file
+    pub fn open_path(&mut self, file: &Path) -> io::Result<()> {
+        if file.extension().is_some_and(|extension| extension == "bkp") {
+            return self.load_project(file);
         }
+        self.load_chosen_image(file)?;
+        self.mode = Mode::SELECTION;
+        Ok(())
+    }
+
+    /// Decode an image, the current one is kept when the file cannot be decoded
+    pub fn load_image(&mut self, file: &Path) -> io::Result<()> {
+        let image = BlueKompassImage::open(file.to_path_buf())
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        self.opened_file = Some(file.to_path_buf());
+        self.image = Some(image);
+        self.histogram_cache = None;
+        self.resolution_pending = false;
+        Ok(())
+    }
+
+    /// Load an image the user opened, the resolution of its file may give the calibration, unlike the image of a
+    /// project that is restored with its own
+    pub fn load_chosen_image(&mut self, file: &Path) -> io::Result<()> {
+        self.load_image(file)?;
+        self.resolution_pending = true;
+        Ok(())
     }
 
+    /// Draw the image with its top left corner on the plot origin, one plot unit per pixel
     pub fn draw_image(&mut self, plot_ui: &mut PlotUi, image_id: TextureId, size: [usize; 2]) {
         plot_ui.image(
             PlotImage::new(
                 image_id,
-                PlotPoint::new(0.0, 0.0),
+                PlotPoint::new(0.5 * size[0] as f64, -0.5 * size[1] as f64),
                 Vec2::new(size[0] as f32, size[1] as f32)
             )
         );
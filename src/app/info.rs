@@ -0,0 +1,102 @@
+use super::BlueKompassApp;
+
+use eframe::egui;
+use egui::{Button, Context};
+
+use bluekompass_core::calibration::Resolution;
+
+impl BlueKompassApp {
+    fn calibrate_from_resolution(&mut self, resolution: Resolution) {
+        if let Some(calibration) = resolution.calibration() {
+            self.calibration.scale = calibration.scale;
+            self.calibration.unit = calibration.unit;
+        }
+    }
+
+    /// Resolution given by the file of the image, once it is loaded
+    pub(super) fn image_resolution(&self) -> Option<Resolution> {
+        self.image.as_ref()?.info()?.resolution
+    }
+
+    /// Calibrate from the resolution of the image just opened, unless a calibration is already set
+    pub fn default_calibration(&mut self) {
+        if !self.resolution_pending {
+            return;
+        }
+        if self.image.as_ref().is_some_and(|image| image.info().is_none()) {
+            // Not loaded yet
+            return;
+        }
+        self.resolution_pending = false;
+        let Some(resolution) = self.image_resolution().filter(|_| !self.calibration.is_calibrated()) else {
+            return;
+        };
+        if let Some([x, y]) = resolution.pixel_size().filter(|_| !resolution.is_square()) {
+            self.notice = Some(format!(
+                "The pixels of the image are not square ({x:.5} × {y:.5} mm), its resolution is not used as calibration"
+            ));
+        }
+        self.calibrate_from_resolution(resolution);
+    }
+
+    pub fn image_info_window(&mut self, ctx: &Context) {
+        let Some(image) = &self.image else {
+            return;
+        };
+        let path = image.path().display().to_string();
+        let info = image.info().cloned();
+        let mut calibrate = None;
+        egui::Window::new("Image info")
+            .open(&mut self.show_image_info)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let Some(info) = &info else {
+                    ui.weak("Loading");
+                    return;
+                };
+                egui::Grid::new("image_info_grid").num_columns(2).show(ui, |ui| {
+                    ui.label("File");
+                    ui.label(&path);
+                    ui.end_row();
+
+                    ui.label("Format");
+                    ui.label(info.format.map_or("Unknown".to_string(), |format| format!("{format:?}").to_uppercase()));
+                    ui.end_row();
+
+                    ui.label("Dimensions");
+                    ui.label(format!("{} × {} px", info.size[0], info.size[1]));
+                    ui.end_row();
+
+                    ui.label("Color type");
+                    ui.label(info.color_name());
+                    ui.end_row();
+
+                    ui.label("Bit depth");
+                    ui.label(format!("{} bits per channel", info.bit_depth()));
+                    ui.end_row();
+
+                    ui.label("Resolution");
+                    match info.resolution.and_then(|resolution| Some((resolution.dpi()?, resolution.pixel_size()?))) {
+                        Some(([x_dpi, y_dpi], [x_size, y_size])) => {
+                            ui.label(format!("{x_dpi:.1} × {y_dpi:.1} dpi, pixels of {x_size:.5} × {y_size:.5} mm"))
+                        }
+                        None => ui.weak("Not in the file"),
+                    };
+                    ui.end_row();
+                });
+                let resolution = info.resolution.filter(|resolution| resolution.calibration().is_some());
+                let button = ui.add_enabled(resolution.is_some(), Button::new("Use as calibration"));
+                let not_square = info.resolution.is_some_and(|resolution| resolution.pixel_size().is_some() && !resolution.is_square());
+                if button.on_disabled_hover_text(if not_square {
+                    "The pixels are not square, a single scale would be wrong along one of the axes"
+                } else {
+                    "The file gives no resolution"
+                }).clicked() {
+                    calibrate = resolution;
+                }
+            });
+        if let Some(resolution) = calibrate {
+            self.calibrate_from_resolution(resolution);
+        }
+    }
+}
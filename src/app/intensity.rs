@@ -0,0 +1,161 @@
+use super::BlueKompassApp;
+
+use eframe::egui;
+use egui::{Color32, Context};
+use egui_plot::{HLine, Legend, Line, Plot, PlotPoints, VLine};
+
+use bluekompass_core::coordinates::plot_to_pixel;
+use bluekompass_core::intensity::{edge_crossings, Histogram, Intensities, Region};
+use bluekompass_core::point::Point;
+use bluekompass_core::shapes::Geometry;
+use crate::render::{BLUE, GREEN, RED};
+
+/// Part of the image counted in the histogram
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HistogramRegion {
+    Image,
+    /// Part of the image shown in the plot
+    View,
+    /// Inside the selected circle
+    Selection,
+}
+
+impl BlueKompassApp {
+    /// Region of the image in pixel coordinates, `None` when it is undefined
+    fn region(&self) -> Option<Region> {
+        match self.histogram_region {
+            HistogramRegion::Image => Some(Region::All),
+            HistogramRegion::View => {
+                let bounds = self.view_bounds;
+                let [min_x, min_y] = bounds.min();
+                let [max_x, max_y] = bounds.max();
+                Some(Region::Rectangle {
+                    min: plot_to_pixel(Point::new(min_x, max_y)),
+                    max: plot_to_pixel(Point::new(max_x, min_y)),
+                })
+            }
+            HistogramRegion::Selection => match self.shapes[self.selected_shape_index?].geometry()? {
+                Geometry::Circle { center, radius } => Some(Region::Disc { center: plot_to_pixel(center), radius }),
+                Geometry::Line(_) => None,
+            },
+        }
+    }
+
+    /// Histogram of the region, only computed again when the region changes
+    fn histogram(&mut self) -> Option<Histogram> {
+        let pixels = self.image.as_ref()?.pixels()?;
+        let size = [pixels.width() as usize, pixels.height() as usize];
+        let region = self.region()?;
+        match &self.histogram_cache {
+            Some((cached, histogram)) if *cached == region => Some(histogram.clone()),
+            _ => {
+                let histogram = Intensities::new(size[0], size[1], pixels.as_raw())?.histogram(region);
+                self.histogram_cache = Some((region, histogram.clone()));
+                Some(histogram)
+            }
+        }
+    }
+
+    pub fn histogram_window(&mut self, ctx: &Context) {
+        if !self.show_histogram {
+            return;
+        }
+        let histogram = self.histogram();
+        let mut open = self.show_histogram;
+        egui::Window::new("Histogram")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut self.histogram_region, HistogramRegion::Image, "Whole image");
+                    ui.selectable_value(&mut self.histogram_region, HistogramRegion::View, "Visible area");
+                    ui.selectable_value(&mut self.histogram_region, HistogramRegion::Selection, "Selected circle");
+                });
+                let Some(histogram) = histogram else {
+                    ui.weak("Select a circle to count the pixels inside it.");
+                    return;
+                };
+                let count: u32 = histogram.luma.iter().sum();
+                ui.label(format!("{count} pixels"));
+
+                let channel = |name: &str, values: &[u32; 256], color: Color32| {
+                    let points: PlotPoints = values.iter().enumerate().map(|(value, &count)| [value as f64, count as f64]).collect();
+                    Line::new(points).name(name).color(color)
+                };
+                Plot::new("histogram_plot")
+                    .legend(Legend::default())
+                    .include_x(0.)
+                    .include_x(255.)
+                    .include_y(0.)
+                    .allow_drag(false)
+                    .allow_scroll(false)
+                    .height(200.)
+                    .show(ui, |plot_ui| {
+                        plot_ui.line(channel("Red", &histogram.red, RED));
+                        plot_ui.line(channel("Green", &histogram.green, GREEN));
+                        plot_ui.line(channel("Blue", &histogram.blue, BLUE));
+                        plot_ui.line(channel("Luma", &histogram.luma, Color32::GRAY));
+                    });
+            });
+        self.show_histogram = open;
+    }
+
+    /// Luma along the selected line, with the distance in calibrated units
+    fn profile(&self) -> Option<Vec<(f64, f64)>> {
+        let Geometry::Line([start, end]) = self.shapes[self.selected_shape_index?].geometry()? else {
+            return None;
+        };
+        let pixels = self.image.as_ref()?.pixels()?;
+        let size = [pixels.width() as usize, pixels.height() as usize];
+        let intensities = Intensities::new(size[0], size[1], pixels.as_raw())?;
+        let profile = intensities.profile([plot_to_pixel(start), plot_to_pixel(end)]);
+        Some(profile.into_iter().map(|(distance, value)| (self.calibration.length(distance), value)).collect())
+    }
+
+    pub fn profile_window(&mut self, ctx: &Context) {
+        if !self.show_profile {
+            return;
+        }
+        let profile = self.profile();
+        let unit = &self.calibration.unit;
+        egui::Window::new("Intensity profile")
+            .open(&mut self.show_profile)
+            .show(ctx, |ui| {
+                let Some(profile) = profile.filter(|profile| !profile.is_empty()) else {
+                    ui.weak("Select a line over the image to plot the intensity along it.");
+                    return;
+                };
+                let crossings = edge_crossings(&profile);
+                let (min, max) = profile.iter()
+                    .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &(_, value)| (min.min(value), max.max(value)));
+
+                Plot::new("profile_plot")
+                    .include_y(0.)
+                    .include_y(255.)
+                    .allow_drag(false)
+                    .allow_scroll(false)
+                    .height(200.)
+                    .show(ui, |plot_ui| {
+                        let points: PlotPoints = profile.iter().map(|&(distance, value)| [distance, value]).collect();
+                        plot_ui.line(Line::new(points).color(BLUE).name("Luma"));
+                        if !crossings.is_empty() {
+                            plot_ui.hline(HLine::new(0.5 * (min + max)).color(Color32::GRAY).name("50%"));
+                        }
+                        for &crossing in &crossings {
+                            plot_ui.vline(VLine::new(crossing).color(RED));
+                        }
+                    });
+
+                ui.label(format!("Min {min:.1}, max {max:.1}"));
+                if crossings.is_empty() {
+                    ui.weak("No edge crossing");
+                }
+                for (index, crossing) in crossings.iter().enumerate() {
+                    let width = index.checked_sub(1).map(|previous| crossing - crossings[previous]);
+                    match width {
+                        Some(width) => ui.label(format!("Edge {} at {crossing:.3} {unit} ({width:.3} {unit} from the previous one)", index + 1)),
+                        None => ui.label(format!("Edge {} at {crossing:.3} {unit}", index + 1)),
+                    };
+                }
+            });
+    }
+}
@@ -0,0 +1,75 @@
+use super::BlueKompassApp;
+
+use eframe::egui;
+use egui::{DragValue, TextEdit, Ui};
+
+use bluekompass_core::layers::Layer;
+use crate::render::color_edit_button;
+
+impl BlueKompassApp {
+    /// A shape is visible when both the shape and its layer are visible
+    pub fn is_visible(&self, shape_index: usize) -> bool {
+        let properties = self.shapes[shape_index].properties();
+        properties.visible && self.layers[properties.layer].visible
+    }
+
+    /// A shape is locked when either the shape or its layer is locked
+    pub fn is_locked(&self, shape_index: usize) -> bool {
+        let properties = self.shapes[shape_index].properties();
+        properties.locked || self.layers[properties.layer].locked
+    }
+
+    fn remove_layer(&mut self, layer_index: usize) {
+        self.layers.remove(layer_index);
+        self.alignment = None;
+        self.comparison_layer = None;
+        for shape in self.shapes.iter_mut() {
+            let properties = shape.properties_mut();
+            if properties.layer == layer_index {
+                properties.layer = 0;
+            } else if properties.layer > layer_index {
+                properties.layer -= 1;
+            }
+        }
+        if self.active_layer >= layer_index {
+            self.active_layer = self.active_layer.saturating_sub(1);
+        }
+    }
+
+    pub fn layers_panel(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.heading("Layers");
+            if ui.button("➕").on_hover_text("Add a layer").clicked() {
+                self.layers.push(Layer::new(format!("Layer {}", self.layers.len())));
+            }
+        });
+
+        let mut removed = None;
+        let mut aligned = None;
+        for (index, layer) in self.layers.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut self.active_layer, index, "").on_hover_text("Active layer");
+                ui.toggle_value(&mut layer.visible, "👁").on_hover_text("Visible");
+                ui.toggle_value(&mut layer.locked, "🔒").on_hover_text("Locked");
+                color_edit_button(ui, &mut layer.color);
+                ui.add(DragValue::new(&mut layer.width).clamp_range(0.5..=20.).speed(0.1))
+                    .on_hover_text("Line width");
+                ui.add(TextEdit::singleline(&mut layer.name).desired_width(80.));
+                if ui.button("📐").on_hover_text("Align the layer on the image with two points").clicked() {
+                    aligned = Some(index);
+                }
+                // The first layer is the default one and cannot be removed
+                if index > 0 && ui.button("🗑").on_hover_text("Remove the layer").clicked() {
+                    removed = Some(index);
+                }
+            });
+        }
+
+        if let Some(index) = aligned {
+            self.start_alignment(index);
+        }
+        if let Some(index) = removed {
+            self.remove_layer(index);
+        }
+    }
+}
@@ -0,0 +1,272 @@
+use super::{BlueKompassApp, FileAction};
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread;
+
+use eframe::egui;
+use egui::{Button, Context, DragValue, ProgressBar, TextEdit};
+
+use bluekompass_core::checkerboard::find_corners;
+use bluekompass_core::distortion::{calibrate, CameraProfile};
+use bluekompass_core::intensity::Intensities;
+use bluekompass_core::orientation::ImageOperation;
+use crate::image_loader::load_image_from_path;
+
+/// Checkerboard images the lens is calibrated from, and the camera profile it gives
+pub struct LensCalibration {
+    images: Vec<PathBuf>,
+    /// Inner corners of the checkerboard, along a row then along a column
+    pattern: [usize; 2],
+    name: String,
+    profile: Option<CameraProfile>,
+    /// What was found in each image at the last calibration
+    report: Vec<String>,
+    /// Updates of the calibration running in the background, with the number of images it started with
+    running: Option<(Receiver<Progress>, usize)>,
+}
+
+impl Default for LensCalibration {
+    fn default() -> Self {
+        Self {
+            images: Vec::new(),
+            pattern: [9, 6],
+            name: "Camera".to_string(),
+            profile: None,
+            report: Vec::new(),
+            running: None,
+        }
+    }
+}
+
+/// Sent by the calibration thread as it goes
+enum Progress {
+    /// What was found in one more image, the distortion is fitted after the last one
+    Image(String),
+    Done(Option<CameraProfile>),
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().into_owned())
+}
+
+impl BlueKompassApp {
+    /// Add a checkerboard image, files whose header cannot be read are refused at once
+    pub fn add_calibration_image(&mut self, file: &Path) -> io::Result<()> {
+        image::image_dimensions(file).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        if !self.lens.images.iter().any(|image| image == file) {
+            self.lens.images.push(file.to_path_buf());
+        }
+        Ok(())
+    }
+
+    pub fn load_camera_profile(&mut self, file: &Path) -> io::Result<()> {
+        let profile = CameraProfile::load(file)?;
+        self.lens.name = profile.name.clone();
+        self.lens.profile = Some(profile);
+        Ok(())
+    }
+
+    pub fn save_camera_profile(&self, file: &Path) -> io::Result<()> {
+        match &self.lens.profile {
+            Some(profile) => profile.save(file),
+            None => Err(io::Error::new(io::ErrorKind::InvalidInput, "no camera profile")),
+        }
+    }
+
+    /// Start calibrating the lens in the background, the window shows the progress
+    fn start_lens_calibration(&mut self, ctx: &Context) {
+        let lens = &mut self.lens;
+        let (sender, receiver) = mpsc::channel();
+        let (images, pattern, name) = (lens.images.clone(), lens.pattern, lens.name.clone());
+        let ctx = ctx.clone();
+        thread::spawn(move || calibrate_lens(images, pattern, name, &sender, &ctx));
+        lens.report.clear();
+        lens.running = Some((receiver, lens.images.len()));
+    }
+
+    /// Take the updates of the calibration running in the background
+    fn poll_lens_calibration(&mut self) {
+        let Some((receiver, _)) = &self.lens.running else {
+            return;
+        };
+        loop {
+            match receiver.try_recv() {
+                Ok(Progress::Image(line)) => self.lens.report.push(line),
+                Ok(Progress::Done(profile)) => {
+                    match profile {
+                        Some(profile) => self.lens.profile = Some(profile),
+                        None => self.error = Some("The lens could not be calibrated from the images".to_string()),
+                    }
+                    self.lens.running = None;
+                    return;
+                }
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => {
+                    self.error = Some("The lens calibration stopped unexpectedly".to_string());
+                    self.lens.running = None;
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Why the image cannot be corrected with the profile, if it cannot
+    fn undistort_blocker(&self, profile: &CameraProfile) -> Option<&'static str> {
+        let Some(sizes) = self.oriented_sizes() else {
+            return Some("Open an image first");
+        };
+        if sizes[0] != profile.image_size {
+            Some("The image is not the size of the calibration images")
+        } else if !self.image_operations.is_empty() {
+            Some("Reset the orientation of the image first")
+        } else {
+            None
+        }
+    }
+
+    pub fn lens_window(&mut self, ctx: &Context) {
+        self.poll_lens_calibration();
+        if !self.show_lens {
+            return;
+        }
+        let blocker = self.lens.profile.as_ref().map(|profile| self.undistort_blocker(profile));
+        let mut open = self.show_lens;
+        let (mut add_image, mut calibrate, mut load, mut save, mut undistort) = (false, false, false, false, false);
+        egui::Window::new("Lens distortion")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let lens = &mut self.lens;
+                ui.strong("Calibration images");
+                let mut removed = None;
+                for (index, image) in lens.images.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(file_name(image));
+                        if ui.button("🗑").on_hover_text("Remove").clicked() {
+                            removed = Some(index);
+                        }
+                    });
+                }
+                if let Some(index) = removed {
+                    lens.images.remove(index);
+                }
+                add_image = ui.button("Add image…").clicked();
+
+                egui::Grid::new("lens_grid").num_columns(2).show(ui, |ui| {
+                    ui.label("Inner corners");
+                    ui.horizontal(|ui| {
+                        ui.add(DragValue::new(&mut lens.pattern[0]).clamp_range(3..=50));
+                        ui.label("×");
+                        ui.add(DragValue::new(&mut lens.pattern[1]).clamp_range(3..=50));
+                    });
+                    ui.end_row();
+
+                    ui.label("Camera");
+                    ui.add(TextEdit::singleline(&mut lens.name).desired_width(120.));
+                    ui.end_row();
+                });
+                calibrate = ui.add_enabled(!lens.images.is_empty() && lens.running.is_none(), Button::new("Calibrate")).clicked();
+                for line in &lens.report {
+                    ui.weak(line);
+                }
+                if let Some((_, count)) = &lens.running {
+                    let done = lens.report.len();
+                    let text = if done < *count {
+                        format!("Looking for the checkerboard in image {} of {count}", done + 1)
+                    } else {
+                        "Fitting the distortion".to_string()
+                    };
+                    ui.add(ProgressBar::new(done as f32 / (*count + 1) as f32).text(text).animate(true));
+                }
+                ui.separator();
+
+                match &lens.profile {
+                    Some(profile) => {
+                        let distortion = &profile.distortion;
+                        let [k1, k2] = distortion.radial;
+                        let [p1, p2] = distortion.tangential;
+                        ui.strong(&profile.name);
+                        ui.label(format!("k1 {k1:.5}  k2 {k2:.5}  p1 {p1:.5}  p2 {p2:.5}"));
+                        ui.label(format!(
+                            "Residual {:.3} px over {} images of {} × {} px",
+                            profile.residual, profile.images, profile.image_size[0], profile.image_size[1],
+                        ));
+                    }
+                    None => {
+                        ui.weak("No camera profile");
+                    }
+                }
+                ui.horizontal(|ui| {
+                    load = ui.button("Load profile…").clicked();
+                    save = ui.add_enabled(lens.profile.is_some(), Button::new("Save profile…")).clicked();
+                });
+                undistort = ui.add_enabled(blocker == Some(None), Button::new("Correct the image")).clicked();
+                if let Some(Some(blocker)) = blocker {
+                    ui.weak(blocker);
+                } else {
+                    ui.weak("Shapes are moved with the image.");
+                }
+            });
+        self.show_lens = open;
+
+        if calibrate {
+            self.start_lens_calibration(ctx);
+        }
+        if undistort {
+            if let Some(profile) = &self.lens.profile {
+                self.transform_image(ImageOperation::Undistort(profile.distortion));
+            }
+        }
+        if add_image {
+            self.open_dialog(FileAction::AddCalibrationImage);
+        } else if load {
+            self.open_dialog(FileAction::OpenCameraProfile);
+        } else if save {
+            self.open_dialog(FileAction::SaveCameraProfile);
+        }
+    }
+}
+
+/// Detect the checkerboard in every calibration image and fit the distortion to all of them, sending what is found
+fn calibrate_lens(images: Vec<PathBuf>, pattern: [usize; 2], name: String, sender: &Sender<Progress>, ctx: &Context) {
+    let send = |progress| {
+        // The window may be gone, the result is dropped then
+        let _ = sender.send(progress);
+        ctx.request_repaint();
+    };
+    let [columns, rows] = pattern;
+    let mut views = Vec::new();
+    let mut size = None;
+    for path in &images {
+        let name = file_name(path);
+        let pixels = match load_image_from_path(path) {
+            Ok((pixels, _)) => pixels,
+            Err(error) => {
+                send(Progress::Image(format!("{name}: {error}")));
+                continue;
+            }
+        };
+        let image_size = [pixels.width() as usize, pixels.height() as usize];
+        if size.is_some_and(|size| size != image_size) {
+            send(Progress::Image(format!("{name}: not the size of the first image")));
+            continue;
+        }
+        let corners = Intensities::new(image_size[0], image_size[1], pixels.as_raw())
+            .and_then(|intensities| find_corners(&intensities, pattern));
+        match corners {
+            Some(corners) => {
+                send(Progress::Image(format!("{name}: {} corners", corners.len())));
+                views.push(corners);
+                size = Some(image_size);
+            }
+            None => send(Progress::Image(format!("{name}: no checkerboard of {columns} × {rows} inner corners"))),
+        }
+    }
+    let profile = size.and_then(|size| {
+        let (distortion, residual) = calibrate(&views, pattern, size)?;
+        Some(CameraProfile { name, image_size: size, distortion, residual, images: views.len() })
+    });
+    send(Progress::Done(profile));
+}
@@ -0,0 +1,116 @@
+use super::{BlueKompassApp, Dragging, Mode};
+
+use eframe::egui;
+use egui::{pos2, vec2, Color32, Context, Id, Order, Pos2, Rect, Stroke, TextureId};
+
+use bluekompass_core::coordinates::plot_to_pixel;
+use bluekompass_core::point::Point;
+use bluekompass_core::shapes::Geometry;
+use crate::render::{to_color32, RED};
+
+/// Number of image pixels shown across the loupe
+const LOUPE_PIXELS: f64 = 15.0;
+/// Side of the loupe in screen points
+const LOUPE_SIZE: f32 = 165.0;
+/// Distance between the cursor and the loupe
+const LOUPE_OFFSET: f32 = 24.0;
+
+impl BlueKompassApp {
+    /// Whether a point is being placed, by building a shape or dragging one of its points
+    fn placing_point(&self) -> bool {
+        matches!(self.mode, Mode::LINE | Mode::CIRCLE) || matches!(self.dragging, Some(Dragging::Point { .. }))
+    }
+
+    /// Magnified view of the pixels and shapes around the cursor
+    pub fn loupe(&self, ctx: &Context, image_id: TextureId, size: [usize; 2]) {
+        if !self.show_loupe || !self.placing_point() {
+            return;
+        }
+        let (Some(cursor), Some(pointer)) = (self.cursor, ctx.pointer_hover_pos()) else {
+            return;
+        };
+
+        // Keep the loupe on screen, on the other side of the cursor near the edges
+        let screen = ctx.screen_rect();
+        let mut min = pointer + vec2(LOUPE_OFFSET, LOUPE_OFFSET);
+        if min.x + LOUPE_SIZE > screen.max.x {
+            min.x = pointer.x - LOUPE_OFFSET - LOUPE_SIZE;
+        }
+        if min.y + LOUPE_SIZE > screen.max.y {
+            min.y = pointer.y - LOUPE_OFFSET - LOUPE_SIZE;
+        }
+        let rect = Rect::from_min_size(min, vec2(LOUPE_SIZE, LOUPE_SIZE));
+
+        // Pixel coordinates of the top left corner, and screen points per pixel
+        let center = plot_to_pixel(cursor);
+        let origin = Point::new(center.x - 0.5 * LOUPE_PIXELS, center.y - 0.5 * LOUPE_PIXELS);
+        let scale = LOUPE_SIZE as f64 / LOUPE_PIXELS;
+        let to_screen = |pixel: Point| {
+            pos2(
+                rect.min.x + ((pixel.x - origin.x) * scale) as f32,
+                rect.min.y + ((pixel.y - origin.y) * scale) as f32,
+            )
+        };
+
+        egui::Area::new(Id::new("loupe"))
+            .order(Order::Tooltip)
+            .fixed_pos(rect.min)
+            .interactable(false)
+            .show(ctx, |ui| {
+                let painter = ui.painter_at(rect);
+                painter.rect_filled(rect, 0., Color32::from_gray(30));
+
+                // Only the part inside the image, the texture would repeat its border otherwise
+                let [width, height] = size.map(|length| length as f64);
+                let image_min = Point::new(origin.x.max(0.), origin.y.max(0.));
+                let image_max = Point::new((origin.x + LOUPE_PIXELS).min(width), (origin.y + LOUPE_PIXELS).min(height));
+                if image_min.x < image_max.x && image_min.y < image_max.y {
+                    let uv = Rect::from_min_max(
+                        pos2((image_min.x / width) as f32, (image_min.y / height) as f32),
+                        pos2((image_max.x / width) as f32, (image_max.y / height) as f32),
+                    );
+                    painter.image(image_id, Rect::from_min_max(to_screen(image_min), to_screen(image_max)), uv, Color32::WHITE);
+
+                    // Pixel grid
+                    let grid = Stroke::new(1.0, Color32::from_black_alpha(60));
+                    for x in (image_min.x.ceil() as usize)..=(image_max.x.floor() as usize) {
+                        let x = x as f64;
+                        painter.line_segment([to_screen(Point::new(x, image_min.y)), to_screen(Point::new(x, image_max.y))], grid);
+                    }
+                    for y in (image_min.y.ceil() as usize)..=(image_max.y.floor() as usize) {
+                        let y = y as f64;
+                        painter.line_segment([to_screen(Point::new(image_min.x, y)), to_screen(Point::new(image_max.x, y))], grid);
+                    }
+                }
+
+                for (index, shape) in self.shapes.iter().enumerate() {
+                    if !self.is_visible(index) {
+                        continue;
+                    }
+                    let layer = &self.layers[shape.properties().layer];
+                    let style = &shape.properties().style;
+                    let color = to_color32(style.color(layer));
+                    let stroke = Stroke::new(style.width(layer), color);
+                    match shape.geometry() {
+                        Some(Geometry::Line([a, b])) => {
+                            painter.line_segment([to_screen(plot_to_pixel(a)), to_screen(plot_to_pixel(b))], stroke);
+                        }
+                        Some(Geometry::Circle { center, radius }) => {
+                            painter.circle_stroke(to_screen(plot_to_pixel(center)), (radius * scale) as f32, stroke);
+                        }
+                        None => (),
+                    }
+                    for &point in shape.as_slice() {
+                        painter.circle_filled(to_screen(plot_to_pixel(point)), 3., color);
+                    }
+                }
+
+                // Crosshair on the exact cursor position
+                let Pos2 { x, y } = to_screen(center);
+                let crosshair = Stroke::new(1.0, RED);
+                painter.line_segment([pos2(x, rect.min.y), pos2(x, rect.max.y)], crosshair);
+                painter.line_segment([pos2(rect.min.x, y), pos2(rect.max.x, y)], crosshair);
+                painter.rect_stroke(rect, 0., Stroke::new(1.0, Color32::WHITE));
+            });
+    }
+}
@@ -0,0 +1,180 @@
+use super::BlueKompassApp;
+
+use eframe::egui;
+use egui::Context;
+use egui_plot::{LineStyle, PlotPoints, PlotUi};
+
+use bluekompass_core::coordinates::Frame;
+use bluekompass_core::orientation::{straightening_rotation, ImageOperation};
+use bluekompass_core::point::Point;
+use bluekompass_core::shapes::Geometry;
+use crate::image_loader::image_size;
+use crate::render::{draw_grips, to_plot_point, to_point, BLUE};
+
+/// Frame of the same origin and axes once its points are mapped
+fn map_frame(frame: &mut Frame, map: &dyn Fn(Point) -> Point) {
+    let (sin, cos) = frame.rotation.sin_cos();
+    let axis = [frame.origin, Point::new(frame.origin.x + cos, frame.origin.y + sin)].map(map);
+    if let Some(mapped) = Frame::new(frame.name.clone(), axis[0], axis) {
+        *frame = mapped;
+    }
+}
+
+impl BlueKompassApp {
+    /// Sizes of the image before each operation, then once they are all applied
+    pub(super) fn oriented_sizes(&self) -> Option<Vec<[usize; 2]>> {
+        let mut size = image_size(self.image.as_ref()?.path())?;
+        let mut sizes = vec![size];
+        for operation in &self.image_operations {
+            size = operation.size(size);
+            sizes.push(size);
+        }
+        Some(sizes)
+    }
+
+    /// Move every shape and frame with the image
+    fn map_everything(&mut self, map: &dyn Fn(Point) -> Point) {
+        for shape in &mut self.shapes {
+            shape.map_points(map);
+        }
+        for frame in &mut self.calibration.frames {
+            map_frame(frame, map);
+        }
+        self.alignment = None;
+        self.deskew_points = None;
+        self.builder.reset();
+        self.histogram_cache = None;
+        self.fit_view = true;
+    }
+
+    pub fn transform_image(&mut self, operation: ImageOperation) {
+        let Some(size) = self.oriented_sizes().and_then(|sizes| sizes.last().copied()) else {
+            return;
+        };
+        self.map_everything(&|point| operation.map_point(point, size));
+        self.image_operations.push(operation);
+    }
+
+    /// Undo every operation on the image
+    pub fn reset_orientation(&mut self) {
+        let Some(sizes) = self.oriented_sizes() else {
+            return;
+        };
+        let operations = std::mem::take(&mut self.image_operations);
+        self.map_everything(&|point| {
+            operations.iter()
+                .zip(&sizes)
+                .rev()
+                .fold(point, |point, (operation, &size)| operation.unmap_point(point, size))
+        });
+    }
+
+    /// Points of the reference line, the picked points or the selected line
+    fn deskew_line(&self) -> Option<[Point; 2]> {
+        if let Some(points) = self.deskew_points.as_ref().filter(|points| points.len() == 2) {
+            return Some([points[0], points[1]]);
+        }
+        match self.shapes[self.selected_shape_index?].geometry()? {
+            Geometry::Line(points) => Some(points),
+            Geometry::Circle { .. } => None,
+        }
+    }
+
+    fn deskew(&mut self, [a, b]: [Point; 2], vertical: bool) {
+        if self.deskew_frame {
+            // X axis along the line, or a quarter turn clockwise from it to make the line vertical
+            let axis = if vertical { Point::new(a.x + (b.y - a.y), a.y - (b.x - a.x)) } else { b };
+            let name = format!("Deskew {}", self.calibration.frames.len() + 1);
+            if let Some(frame) = Frame::new(name, Point::new(0., 0.), [a, axis]) {
+                self.calibration.frames.push(frame);
+                self.calibration.active_frame = Some(self.calibration.frames.len() - 1);
+            }
+        } else {
+            let angle = (b.y - a.y).atan2(b.x - a.x).to_degrees();
+            let rotation = straightening_rotation(angle, vertical);
+            if rotation != 0. {
+                self.transform_image(ImageOperation::Rotate(rotation));
+            }
+        }
+        self.deskew_points = None;
+    }
+
+    /// Pick the two points of the reference line, returns whether they are being picked
+    pub fn pick_deskew_point(&mut self, plot_ui: &mut PlotUi) -> bool {
+        let Some(points) = self.deskew_points.as_mut().filter(|points| points.len() < 2) else {
+            return false;
+        };
+        if plot_ui.response().clicked() {
+            if let Some(pos) = plot_ui.pointer_coordinate() {
+                points.push(to_point(pos));
+            }
+        }
+        true
+    }
+
+    pub fn draw_deskew(&self, plot_ui: &mut PlotUi) {
+        let Some(points) = self.deskew_points.as_ref().filter(|points| !points.is_empty()) else {
+            return;
+        };
+        if let [a, b] = points[..] {
+            plot_ui.line(
+                egui_plot::Line::new(PlotPoints::Owned(vec![to_plot_point(a), to_plot_point(b)]))
+                    .color(BLUE)
+                    .style(LineStyle::dashed_dense())
+            );
+        }
+        draw_grips(plot_ui, points, BLUE, 5.);
+    }
+
+    pub fn deskew_window(&mut self, ctx: &Context) {
+        if !self.show_deskew {
+            return;
+        }
+        let line = self.deskew_line();
+        let mut open = self.show_deskew;
+        egui::Window::new("Deskew")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                match (&self.deskew_points, line) {
+                    (Some(points), _) if points.len() < 2 => {
+                        ui.label(format!("Click point {} of the reference line", points.len() + 1));
+                    }
+                    (_, Some([a, b])) => {
+                        let angle = (b.y - a.y).atan2(b.x - a.x).to_degrees();
+                        let source = if self.deskew_points.is_some() { "Picked line" } else { "Selected line" };
+                        ui.label(format!("{source} at {angle:.3}° from the image X axis"));
+                    }
+                    _ => {
+                        ui.weak("Select a line or pick two points along an edge of the part");
+                    }
+                }
+                if ui.button("Pick two points").clicked() {
+                    self.deskew_points = Some(Vec::new());
+                }
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.deskew_frame, false, "Rotate the image");
+                    ui.radio_value(&mut self.deskew_frame, true, "Add a datum frame");
+                });
+                ui.horizontal(|ui| {
+                    if let Some(line) = line {
+                        if ui.button("Make horizontal").clicked() {
+                            self.deskew(line, false);
+                        }
+                        if ui.button("Make vertical").clicked() {
+                            self.deskew(line, true);
+                        }
+                    }
+                });
+                if !self.deskew_frame {
+                    ui.weak("Shapes are rotated with the image.");
+                }
+            });
+        if !open {
+            self.deskew_points = None;
+        }
+        self.show_deskew = open;
+    }
+}
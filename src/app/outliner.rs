@@ -0,0 +1,96 @@
+use super::BlueKompassApp;
+
+use eframe::egui;
+use egui::{ComboBox, Id, ScrollArea, TextEdit, Ui};
+
+use bluekompass_core::measure::calibrated;
+use bluekompass_core::shapes::ShapeKind;
+use crate::render::to_plot_point;
+
+impl BlueKompassApp {
+    fn move_shape(&mut self, from: usize, to: usize) {
+        let shape = self.shapes.remove(from);
+        self.shapes.insert(to, shape);
+
+        // Keep the selection on the same shape
+        self.dragging = None;
+        if let Some(selection_index) = self.selected_shape_index {
+            self.selected_shape_index = match selection_index {
+                i if i == from => Some(to),
+                i if from < i && i <= to => Some(i - 1),
+                i if to <= i && i < from => Some(i + 1),
+                i => Some(i),
+            };
+        }
+    }
+
+    fn focus_shape(&mut self, shape_index: usize) {
+        self.unselect_shape();
+        self.select_shape(shape_index);
+        self.center_on = Some(to_plot_point(self.shapes[shape_index].center()));
+    }
+
+    pub fn outliner(&mut self, ui: &mut Ui) {
+        ui.heading("Shapes");
+        ComboBox::from_label("Filter")
+            .selected_text(self.outliner_filter.map_or("All", |kind| kind.name()))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut self.outliner_filter, None, "All");
+                for kind in ShapeKind::ALL {
+                    ui.selectable_value(&mut self.outliner_filter, Some(kind), kind.name());
+                }
+            });
+        ui.separator();
+
+        let mut focused = None;
+        let mut moved = None;
+        let layers = &self.layers;
+        ScrollArea::vertical().show(ui, |ui| {
+            for (index, shape) in self.shapes.iter_mut().enumerate() {
+                if self.outliner_filter.is_some_and(|kind| kind != shape.kind()) {
+                    continue;
+                }
+                let is_selected = self.selected_shape_index == Some(index);
+                let kind = shape.kind().name();
+                let dimensions = shape.measurements()
+                    .iter()
+                    .map(|&(name, value)| format!("{name}: {:.2}", calibrated(name, value, &self.calibration).0))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let properties = shape.properties_mut();
+
+                let row = ui.horizontal(|ui| {
+                    ui.dnd_drag_source(Id::new(("outliner", properties.id)), index, |ui| {
+                        ui.label("☰");
+                    });
+                    ui.toggle_value(&mut properties.visible, "👁").on_hover_text("Visible");
+                    ui.toggle_value(&mut properties.locked, "🔒").on_hover_text("Locked");
+                    ui.add(TextEdit::singleline(&mut properties.name).desired_width(80.));
+                    ComboBox::from_id_source(("layer", properties.id))
+                        .width(60.)
+                        .selected_text(&layers[properties.layer].name)
+                        .show_ui(ui, |ui| {
+                            for (layer_index, layer) in layers.iter().enumerate() {
+                                ui.selectable_value(&mut properties.layer, layer_index, &layer.name);
+                            }
+                        });
+                    if ui.selectable_label(is_selected, kind).on_hover_text(&dimensions).clicked() {
+                        focused = Some(index);
+                    }
+                    ui.weak(&dimensions);
+                }).response;
+
+                if let Some(from) = row.dnd_release_payload::<usize>() {
+                    moved = Some((*from, index));
+                }
+            }
+        });
+
+        if let Some(index) = focused {
+            self.focus_shape(index);
+        }
+        if let Some((from, to)) = moved {
+            self.move_shape(from, to);
+        }
+    }
+}
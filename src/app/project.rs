@@ -0,0 +1,135 @@
+use super::{BlueKompassApp, FileAction, Mode};
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use image::ImageFormat;
+
+use bluekompass_core::export::{shape_rows, to_csv, to_dxf, to_json, to_svg, to_tsv, ShapeRow, SvgImage};
+use bluekompass_core::project::Project;
+use crate::image_loader::image_size;
+use crate::raster;
+
+impl BlueKompassApp {
+    pub fn load_project(&mut self, file: &Path) -> io::Result<()> {
+        let mut project = Project::load(file)?;
+        let size = project.image.as_deref().and_then(image_size);
+        let upgraded = project.upgrade(size);
+        if !upgraded {
+            self.error = Some(format!(
+                "{}: the image of this older project is missing, its shapes are not placed on the image",
+                file.display(),
+            ));
+        }
+        self.project_version = project.version;
+        self.shapes = project.shapes();
+        self.layers = project.layers;
+        self.default_style = project.default_style;
+        self.calibration = project.calibration;
+        self.deviation_tolerance = project.deviation_tolerance;
+        self.pairing_distance = project.pairing_distance;
+        self.adjustments = project.adjustments;
+        self.image_operations = project.image_operations;
+        self.active_layer = 0;
+        self.next_shape_id = self.shapes.iter()
+            .map(|shape| shape.properties().id)
+            .max()
+            .unwrap_or(0);
+        self.selected_shape_index = None;
+        self.dragging = None;
+        self.alignment = None;
+        self.show_comparison = false;
+        self.comparison_layer = None;
+        self.builder.reset();
+        if let Some(image) = &project.image {
+            if size.is_none() {
+                // An older project already says its image is missing
+                if upgraded {
+                    self.error = Some(format!("{}: the image of the project cannot be read", image.display()));
+                }
+                self.image = None;
+            } else if let Err(error) = self.load_image(image) {
+                self.error = Some(format!("{}: {error}", image.display()));
+                self.image = None;
+            }
+        }
+        self.project_file = Some(file.to_path_buf());
+        self.mode = Mode::SELECTION;
+        Ok(())
+    }
+
+    pub fn save_project(&mut self, file: &Path) -> io::Result<()> {
+        let image = self.image.as_ref().map(|image| image.path().to_path_buf());
+        let project = Project {
+            calibration: self.calibration.clone(),
+            deviation_tolerance: self.deviation_tolerance,
+            pairing_distance: self.pairing_distance,
+            adjustments: self.adjustments.clone(),
+            image_operations: self.image_operations.clone(),
+            version: self.project_version,
+            ..Project::new(image, &self.layers, &self.default_style, &self.shapes)
+        };
+        project.save(file)?;
+        self.project_file = Some(file.to_path_buf());
+        Ok(())
+    }
+
+    pub fn save(&mut self) {
+        match self.project_file.clone() {
+            Some(file) => {
+                if let Err(error) = self.save_project(&file) {
+                    self.error = Some(format!("{}: {error}", file.display()));
+                }
+            }
+            None => self.open_dialog(FileAction::SaveProject),
+        }
+    }
+
+    pub fn export_svg(&self, file: &Path) -> io::Result<()> {
+        let image = self.image.as_ref()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no image is opened"))?;
+        let size = image.size()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "the image is not loaded yet"))?;
+        if self.image_operations.is_empty() {
+            return fs::write(file, to_svg(&self.shapes, &self.layers, size, Some(SvgImage::File(image.path()))));
+        }
+        // The file is not what the shapes are drawn on once the image is transformed, its pixels are embedded
+        let pixels = image.pixels()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "the image is not loaded yet"))?;
+        let mut png = Vec::new();
+        pixels.write_to(&mut io::Cursor::new(&mut png), ImageFormat::Png).map_err(io::Error::other)?;
+        fs::write(file, to_svg(&self.shapes, &self.layers, size, Some(SvgImage::Png(&png))))
+    }
+
+    pub fn export_dxf(&self, file: &Path) -> io::Result<()> {
+        fs::write(file, to_dxf(&self.shapes, &self.layers, &self.calibration))
+    }
+
+    /// Export the image with the shapes burned in, at the scale of the raster options
+    pub fn export_image(&self, file: &Path) -> io::Result<()> {
+        let image = self.image.as_ref()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no image is opened"))?;
+        let pixels = image.pixels()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "the image is not loaded yet"))?;
+        let rendered = raster::render(pixels, &self.shapes, &self.layers, &self.calibration, &self.raster_options)?;
+        raster::save(rendered, file)
+    }
+
+    fn shape_rows(&self) -> Vec<ShapeRow> {
+        shape_rows(&self.shapes, &self.layers, &self.calibration)
+    }
+
+    pub fn export_csv(&self, file: &Path) -> io::Result<()> {
+        fs::write(file, to_csv(&self.shape_rows()))
+    }
+
+    pub fn export_json(&self, file: &Path) -> io::Result<()> {
+        fs::write(file, to_json(&self.shape_rows())?)
+    }
+
+    /// Measurements as tab-separated values, ready to be pasted in a spreadsheet
+    pub fn measurements_text(&self) -> String {
+        to_tsv(&self.shape_rows())
+    }
+}
@@ -0,0 +1,201 @@
+use super::BlueKompassApp;
+
+use eframe::egui;
+use egui::{Context, DragValue, TextEdit};
+use egui_plot::{LineStyle, PlotPoints, PlotUi};
+
+use bluekompass_core::coordinates::plot_to_pixel;
+use bluekompass_core::orientation;
+use bluekompass_core::point::Point;
+use crate::render::{draw_grips, to_plot_point, to_point, BLUE};
+
+const CORNERS: [&str; 4] = ["top left", "top right", "bottom right", "bottom left"];
+
+/// Four corners of a rectangle of known size, to look at the image from the front
+pub struct Rectification {
+    /// Corners then check points
+    points: Vec<Point>,
+    /// Positions of the check points from the top left corner, along the width and the height
+    positions: Vec<[f64; 2]>,
+    /// The next click adds a check point
+    adding_check: bool,
+    width: f64,
+    height: f64,
+    unit: String,
+    /// Reprojection residual of the last rectification in pixels and in units, with the number of points fitted
+    residual: Option<(f64, f64, usize)>,
+}
+
+impl Rectification {
+    pub fn new(unit: &str) -> Self {
+        Self {
+            points: Vec::new(),
+            positions: Vec::new(),
+            adding_check: false,
+            width: 100.,
+            height: 100.,
+            unit: unit.to_string(),
+            residual: None,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.points.clear();
+        self.positions.clear();
+        self.adding_check = false;
+    }
+}
+
+impl BlueKompassApp {
+    fn rectify(&mut self) {
+        let Some(rectification) = &self.rectification else {
+            return;
+        };
+        let Some(size) = self.oriented_sizes().and_then(|sizes| sizes.last().copied()) else {
+            return;
+        };
+        let pixels: Vec<Point> = rectification.points.iter().map(|&point| plot_to_pixel(point)).collect();
+        let Some(Ok(corners)) = pixels.get(..4).map(<[Point; 4]>::try_from) else {
+            return;
+        };
+        let checks: Vec<(Point, Point)> = pixels[4..].iter()
+            .zip(&rectification.positions)
+            .map(|(&pixel, &[x, y])| (pixel, Point::new(x, y)))
+            .collect();
+        let rectangle = [rectification.width, rectification.height];
+        let Some(rectified) = orientation::rectification(corners, rectangle, &checks, size) else {
+            self.error = Some("The points do not make a rectangle that can be rectified".to_string());
+            return;
+        };
+        let unit = rectification.unit.clone();
+
+        self.transform_image(rectified.operation);
+        self.calibration.scale = 1. / rectified.scale;
+        self.calibration.unit = unit;
+        if let Some(rectification) = &mut self.rectification {
+            rectification.residual = Some((rectified.residual, rectified.residual / rectified.scale, pixels.len()));
+            rectification.clear();
+        }
+    }
+
+    /// Pick the corners of the rectangle then the check points, returns whether a point is being picked
+    pub fn pick_rectification_point(&mut self, plot_ui: &mut PlotUi) -> bool {
+        let Some(rectification) = self.rectification.as_mut()
+            .filter(|rectification| rectification.points.len() < 4 || rectification.adding_check)
+        else {
+            return false;
+        };
+        if plot_ui.response().clicked() {
+            if let Some(pos) = plot_ui.pointer_coordinate() {
+                rectification.points.push(to_point(pos));
+                if rectification.points.len() > 4 {
+                    rectification.positions.push([0., 0.]);
+                    rectification.adding_check = false;
+                }
+            }
+        }
+        true
+    }
+
+    pub fn draw_rectification(&self, plot_ui: &mut PlotUi) {
+        let Some(rectification) = &self.rectification else {
+            return;
+        };
+        let points = &rectification.points;
+        if points.len() > 1 {
+            let corners = &points[..points.len().min(4)];
+            let mut outline: Vec<_> = corners.iter().map(|&point| to_plot_point(point)).collect();
+            if corners.len() == 4 {
+                outline.push(to_plot_point(points[0]));
+            }
+            plot_ui.line(egui_plot::Line::new(PlotPoints::Owned(outline)).color(BLUE).style(LineStyle::dashed_dense()));
+        }
+        draw_grips(plot_ui, points, BLUE, 5.);
+    }
+
+    pub fn rectification_window(&mut self, ctx: &Context) {
+        let Some(rectification) = &mut self.rectification else {
+            return;
+        };
+        let mut open = true;
+        let mut rectify = false;
+        egui::Window::new("Perspective rectification")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                for (index, corner) in CORNERS.iter().enumerate() {
+                    let text = format!("{}. Click the {corner} corner of the rectangle", index + 1);
+                    if index < rectification.points.len() {
+                        ui.weak(format!("{text} ✔"));
+                    } else if index == rectification.points.len() {
+                        ui.label(format!("{text} ⬅"));
+                    } else {
+                        ui.weak(text);
+                    }
+                }
+                egui::Grid::new("rectification_grid").num_columns(2).show(ui, |ui| {
+                    ui.label("Width");
+                    ui.add(DragValue::new(&mut rectification.width).clamp_range(1e-6..=f64::MAX).speed(0.1));
+                    ui.end_row();
+
+                    ui.label("Height");
+                    ui.add(DragValue::new(&mut rectification.height).clamp_range(1e-6..=f64::MAX).speed(0.1));
+                    ui.end_row();
+
+                    ui.label("Unit");
+                    ui.add(TextEdit::singleline(&mut rectification.unit).desired_width(60.));
+                    ui.end_row();
+                });
+
+                ui.separator();
+                ui.strong("Check points");
+                let mut removed = None;
+                for (index, [x, y]) in rectification.positions.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{}.", index + 1));
+                        ui.add(DragValue::new(x).speed(0.1).prefix("x "));
+                        ui.add(DragValue::new(y).speed(0.1).prefix("y "));
+                        ui.label(&rectification.unit);
+                        if ui.button("🗑").on_hover_text("Remove").clicked() {
+                            removed = Some(index);
+                        }
+                    });
+                }
+                if let Some(index) = removed {
+                    rectification.positions.remove(index);
+                    rectification.points.remove(4 + index);
+                }
+                if rectification.adding_check {
+                    ui.label("Click the check point ⬅");
+                } else if ui.add_enabled(rectification.points.len() >= 4, egui::Button::new("Add check point")).clicked() {
+                    rectification.adding_check = true;
+                }
+                ui.weak("Other points of the plane at known positions from the top left corner, fitted with the corners.");
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    let ready = rectification.points.len() >= 4 && !rectification.adding_check;
+                    rectify = ui.add_enabled(ready, egui::Button::new("Rectify")).clicked();
+                    if ui.button("Pick again").clicked() {
+                        rectification.clear();
+                    }
+                });
+                match rectification.residual {
+                    Some((pixels, length, count)) if count > 4 => {
+                        ui.label(format!("Reprojection residual {pixels:.2} px, {length:.3} {} over {count} points", rectification.unit));
+                    }
+                    Some(_) => {
+                        ui.weak("The four corners are fitted exactly, add check points to get a reprojection residual.");
+                    }
+                    None => {}
+                }
+                ui.weak("The image, the shapes and the calibration are rectified.");
+            });
+        if rectify {
+            self.rectify();
+        }
+        if !open {
+            self.rectification = None;
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use super::BlueKompassApp;
+
+use std::cmp::Ordering;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use eframe::egui;
+use egui::Context;
+use egui_plot::{LineStyle, PlotPoints, PlotUi};
+
+use bluekompass_core::color::Color;
+use bluekompass_core::import::{from_dxf, DxfImport};
+use bluekompass_core::layers::Layer;
+use bluekompass_core::point::Point;
+use bluekompass_core::style::Style;
+use bluekompass_core::transform::Similarity;
+use crate::render::{draw_grips, to_plot_point, to_point, BLUE};
+
+const REFERENCE_COLOR: Color = Color::from_rgb(255, 0, 255);
+
+/// Distance under which a picked point snaps on a point of the layer
+const SNAP_DISTANCE: f64 = 10.;
+
+const STEPS: [&str; 4] = [
+    "Click a first point of the layer",
+    "Click a second point of the layer",
+    "Click where the first point is on the image",
+    "Click where the second point is on the image",
+];
+
+/// What a DXF import could not keep as drawn, `None` when everything was
+fn import_notice(approximated: usize, skipped: &[String]) -> Option<String> {
+    let mut lines = Vec::new();
+    if approximated > 0 {
+        lines.push(format!("{approximated} arcs and bulged polyline segments are drawn as straight segments"));
+    }
+    if !skipped.is_empty() {
+        let mut kinds: Vec<&str> = skipped.iter().map(String::as_str).collect();
+        kinds.sort_unstable();
+        kinds.dedup();
+        lines.push(format!("{} entities are not imported: {}", skipped.len(), kinds.join(", ")));
+    }
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+/// Two-point alignment of a layer, two points of the layer are picked then the points of the image they go to
+pub struct Alignment {
+    layer: usize,
+    points: Vec<Point>,
+}
+
+impl BlueKompassApp {
+    /// Import the geometry of a DXF file on a new locked layer and start aligning it
+    ///
+    /// The file is read in pixels in the active frame, along the chosen Y axis, as shapes are exported.
+    pub fn import_dxf(&mut self, file: &Path) -> io::Result<()> {
+        let DxfImport { mut shapes, approximated, skipped } = from_dxf(&fs::read_to_string(file)?)?;
+        if shapes.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "no LINE, CIRCLE, ARC or LWPOLYLINE entity"));
+        }
+        self.notice = import_notice(approximated, &skipped).map(|notice| format!("{}: {notice}", file.display()));
+
+        let name = file.file_stem().map_or("DXF".into(), |stem| stem.to_string_lossy());
+        let mut layer = Layer::new(format!("Reference {name}"));
+        layer.color = REFERENCE_COLOR;
+        layer.width = 1.5;
+        layer.locked = true;
+        layer.reference = true;
+        self.layers.push(layer);
+
+        let layer_index = self.layers.len() - 1;
+        let style = Style { show_grips: false, ..Style::default() };
+        for shape in &mut shapes {
+            shape.map_points(&|point| self.calibration.unframe_point(point));
+        }
+        for shape in shapes {
+            self.add_shape_on_layer(shape, layer_index, style.clone());
+        }
+        self.start_alignment(layer_index);
+        Ok(())
+    }
+
+    pub fn start_alignment(&mut self, layer: usize) {
+        self.builder.reset();
+        self.alignment = Some(Alignment { layer, points: Vec::new() });
+    }
+
+    /// Nearest defining point or center of a shape of the layer, if close enough
+    fn snap_on_layer(&self, layer: usize, pos: Point) -> Option<Point> {
+        let distance = |point: &Point| (point.to_vec2() - pos.to_vec2()).length();
+        self.shapes.iter()
+            .filter(|shape| shape.properties().layer == layer)
+            .flat_map(|shape| shape.as_slice().iter().copied().chain([shape.center()]))
+            .filter(|point| distance(point) < SNAP_DISTANCE)
+            .min_by(|a, b| distance(a).partial_cmp(&distance(b)).unwrap_or(Ordering::Equal))
+    }
+
+    fn align_layer(&mut self, layer: usize, points: &[Point]) {
+        let Some(similarity) = Similarity::from_two_points([points[0], points[1]], [points[2], points[3]]) else {
+            self.error = Some("The two points of an alignment must be distinct".to_string());
+            return;
+        };
+        for shape in self.shapes.iter_mut().filter(|shape| shape.properties().layer == layer) {
+            shape.map_points(&|point| similarity.apply(point));
+        }
+    }
+
+    /// Pick the points of an alignment in progress, returns whether an alignment is in progress
+    pub fn pick_alignment_point(&mut self, plot_ui: &mut PlotUi) -> bool {
+        let Some(alignment) = &self.alignment else {
+            return false;
+        };
+        if plot_ui.response().clicked() {
+            if let Some(pos) = plot_ui.pointer_coordinate() {
+                let mut point = to_point(pos);
+                if alignment.points.len() < 2 {
+                    point = self.snap_on_layer(alignment.layer, point).unwrap_or(point);
+                }
+
+                let Some(alignment) = &mut self.alignment else {
+                    return true;
+                };
+                alignment.points.push(point);
+                if alignment.points.len() == STEPS.len() {
+                    let (layer, points) = (alignment.layer, std::mem::take(&mut alignment.points));
+                    self.alignment = None;
+                    self.align_layer(layer, &points);
+                }
+            }
+        }
+        true
+    }
+
+    /// Draw the picked points, each point of the layer is linked to its point on the image
+    pub fn draw_alignment(&self, plot_ui: &mut PlotUi) {
+        let Some(alignment) = &self.alignment else {
+            return;
+        };
+        for (from, to) in alignment.points.iter().zip(alignment.points.iter().skip(2)) {
+            plot_ui.line(
+                egui_plot::Line::new(PlotPoints::Owned(vec![to_plot_point(*from), to_plot_point(*to)]))
+                    .color(BLUE)
+                    .style(LineStyle::dashed_dense())
+            );
+        }
+        draw_grips(plot_ui, &alignment.points, BLUE, 5.);
+    }
+
+    pub fn alignment_window(&mut self, ctx: &Context) {
+        let Some(alignment) = &mut self.alignment else {
+            return;
+        };
+        let mut open = true;
+        egui::Window::new(format!("Align {}", self.layers[alignment.layer].name))
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                for (index, step) in STEPS.iter().enumerate() {
+                    if index < alignment.points.len() {
+                        ui.weak(format!("✔ {step}"));
+                    } else if index == alignment.points.len() {
+                        ui.strong(format!("➡ {step}"));
+                    } else {
+                        ui.label(format!("   {step}"));
+                    }
+                }
+                ui.weak("Points of the layer snap on the nearest point or center of its shapes.");
+                if ui.button("Restart").clicked() {
+                    alignment.points.clear();
+                }
+            });
+        if !open {
+            self.alignment = None;
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use super::{BlueKompassApp, Mode};
+
+use eframe::egui;
+use egui::{Context, Event, Modifiers};
+
+use crate::keymap::{Action, Keymap};
+
+impl BlueKompassApp {
+    pub(super) fn set_mode(&mut self, mode: Mode) {
+        self.mode = mode;
+        self.builder.reset();
+        self.dragging = None;
+    }
+
+    fn run_action(&mut self, action: Action) {
+        match action {
+            Action::DragMode => self.set_mode(Mode::DRAG),
+            Action::SelectionMode => self.set_mode(Mode::SELECTION),
+            Action::LineMode => self.set_mode(Mode::LINE),
+            Action::CircleMode => self.set_mode(Mode::CIRCLE),
+            Action::Cancel => self.builder.reset(),
+            Action::Delete => self.remove_shape(),
+        }
+    }
+
+    pub fn handle_shortcuts(&mut self, ctx: &Context) {
+        // Keys are captured by the keymap editor or typed in a text field
+        if self.capturing_action.is_some() || ctx.wants_keyboard_input() || self.image.is_none() {
+            return;
+        }
+        // Only keys pressed without Ctrl or Cmd run the actions, the key is consumed once it has
+        let actions: Vec<Action> = ctx.input_mut(|i| {
+            Action::ALL.into_iter()
+                .filter(|&action| self.keymap.key(action).is_some_and(|key| i.consume_key(Modifiers::NONE, key)))
+                .collect()
+        });
+        for action in actions {
+            self.run_action(action);
+        }
+    }
+
+    pub fn keymap_window(&mut self, ctx: &Context) {
+        if let Some(action) = self.capturing_action {
+            let pressed = ctx.input(|i| {
+                i.events.iter().find_map(|event| match event {
+                    Event::Key { key, pressed: true, .. } => Some(*key),
+                    _ => None,
+                })
+            });
+            if let Some(key) = pressed {
+                self.keymap.bind(action, key);
+                self.capturing_action = None;
+                self.save_keymap();
+            }
+        }
+
+        let mut open = self.show_keymap;
+        egui::Window::new("Keyboard shortcuts")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("keymap").num_columns(3).striped(true).show(ui, |ui| {
+                    for action in Action::ALL {
+                        ui.label(action.name());
+                        let text = if self.capturing_action == Some(action) {
+                            "Press a key…"
+                        } else {
+                            self.keymap.key(action).map_or("None", |key| key.name())
+                        };
+                        if ui.button(text).clicked() {
+                            self.capturing_action = Some(action);
+                        }
+                        if ui.small_button("🗑").on_hover_text("Remove the shortcut").clicked() {
+                            self.keymap.unbind(action);
+                            self.save_keymap();
+                        }
+                        ui.end_row();
+                    }
+                });
+                if ui.button("Reset to defaults").clicked() {
+                    self.keymap = Keymap::default();
+                    self.save_keymap();
+                }
+            });
+        self.show_keymap = open;
+        if !open {
+            self.capturing_action = None;
+        }
+    }
+
+    fn save_keymap(&mut self) {
+        if let Err(error) = self.keymap.save() {
+            self.error = Some(format!("Cannot save the keyboard shortcuts: {error}"));
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use super::BlueKompassApp;
+
+use eframe::egui;
+use egui::Ui;
+use egui_plot::PlotUi;
+
+use bluekompass_core::adjustments::luma;
+use bluekompass_core::coordinates::plot_to_pixel;
+use crate::render::to_point;
+
+impl BlueKompassApp {
+    pub fn track_cursor(&mut self, plot_ui: &PlotUi) {
+        self.zoom = plot_ui.transform().dpos_dvalue_x();
+        self.cursor = plot_ui.pointer_coordinate()
+            .filter(|_| plot_ui.response().contains_pointer())
+            .map(to_point);
+    }
+
+    /// Mode, cursor position, value of the pixel under it and zoom
+    pub fn status_bar(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label(format!("Mode: {}", self.mode.name()));
+            let points = self.builder.point_count();
+            if points > 0 {
+                ui.label(format!("{points} point{}", if points > 1 { "s" } else { "" }));
+            }
+            ui.separator();
+
+            let pixels = self.image.as_ref().and_then(|image| image.pixels());
+            match (self.cursor, pixels) {
+                (Some(cursor), Some(pixels)) => {
+                    let pixel = plot_to_pixel(cursor);
+                    let position = self.calibration.frame_point(cursor);
+                    ui.monospace(format!("x {:.1} y {:.1} px", position.x, position.y));
+                    if self.calibration.is_calibrated() {
+                        let position = self.calibration.point(cursor);
+                        ui.monospace(format!("x {:.3} y {:.3} {}", position.x, position.y, self.calibration.unit));
+                    }
+                    ui.separator();
+                    let inside = pixel.x >= 0. && pixel.y >= 0.;
+                    match pixels.get_pixel_checked(pixel.x as u32, pixel.y as u32).filter(|_| inside) {
+                        Some(&image::Rgba([r, g, b, _])) => {
+                            ui.monospace(format!("RGB {r:3} {g:3} {b:3}  gray {:3}", luma([r, g, b])));
+                        }
+                        None => {
+                            ui.weak("Outside the image");
+                        }
+                    }
+                }
+                _ => {
+                    ui.weak("No cursor over the image");
+                }
+            }
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                ui.label(format!("Zoom {:.0}%", 100. * self.zoom));
+                self.frame_selector(ui);
+            });
+        });
+    }
+}
@@ -0,0 +1,80 @@
+use super::BlueKompassApp;
+
+use eframe::egui;
+use egui::{ComboBox, Context, DragValue, Ui};
+
+use bluekompass_core::color::Color;
+use bluekompass_core::style::{Dash, Style};
+use crate::render::color_edit_button;
+
+fn style_editor(ui: &mut Ui, style: &mut Style) {
+    egui::Grid::new(ui.next_auto_id()).num_columns(2).show(ui, |ui| {
+        ui.label("Color");
+        ui.horizontal(|ui| {
+            let mut by_layer = style.color.is_none();
+            if ui.checkbox(&mut by_layer, "By layer").changed() {
+                style.color = if by_layer { None } else { Some(Color::BLACK) };
+            }
+            if let Some(color) = &mut style.color {
+                color_edit_button(ui, color);
+            }
+        });
+        ui.end_row();
+
+        ui.label("Width");
+        ui.horizontal(|ui| {
+            let mut by_layer = style.width.is_none();
+            if ui.checkbox(&mut by_layer, "By layer").changed() {
+                style.width = if by_layer { None } else { Some(3.0) };
+            }
+            if let Some(width) = &mut style.width {
+                ui.add(DragValue::new(width).clamp_range(0.5..=20.).speed(0.1));
+            }
+        });
+        ui.end_row();
+
+        ui.label("Dash");
+        ComboBox::from_id_source(ui.next_auto_id())
+            .selected_text(style.dash.name())
+            .show_ui(ui, |ui| {
+                for dash in Dash::ALL {
+                    ui.selectable_value(&mut style.dash, dash, dash.name());
+                }
+            });
+        ui.end_row();
+
+        ui.label("Grips");
+        ui.checkbox(&mut style.show_grips, "Visible");
+        ui.end_row();
+
+        ui.label("Marker size");
+        ui.add(DragValue::new(&mut style.marker_size).clamp_range(2.0..=20.).speed(0.1));
+        ui.end_row();
+    });
+}
+
+impl BlueKompassApp {
+    pub fn style_panel(&mut self, ui: &mut Ui) {
+        if let Some(selection_index) = self.selected_shape_index {
+            let properties = self.shapes[selection_index].properties_mut();
+            ui.heading(format!("Style of {}", properties.name));
+            style_editor(ui, &mut properties.style);
+            if ui.button("Use as default").clicked() {
+                self.default_style = properties.style.clone();
+            }
+            ui.separator();
+        }
+    }
+
+    pub fn default_style_window(&mut self, ctx: &Context) {
+        egui::Window::new("Default style")
+            .open(&mut self.show_default_style)
+            .resizable(false)
+            .show(ctx, |ui| {
+                style_editor(ui, &mut self.default_style);
+                if ui.button("Reset").clicked() {
+                    self.default_style = Style::default();
+                }
+            });
+    }
+}
@@ -0,0 +1,133 @@
+use super::BlueKompassApp;
+
+use std::fmt::Write;
+
+use eframe::egui;
+use egui::{Context, DragValue, RichText, Ui};
+
+use bluekompass_core::measure::{measure, measure_shape, Measurement};
+use bluekompass_core::tolerance::Tolerance;
+use crate::render::{GREEN, RED};
+
+fn status(ui: &mut Ui, measurement: &Measurement) {
+    match measurement.passed() {
+        Some(true) => ui.label(RichText::new("PASS").color(GREEN)),
+        Some(false) => ui.label(RichText::new("FAIL").color(RED)),
+        None => ui.label(""),
+    };
+}
+
+impl BlueKompassApp {
+    /// Nominal value and deviations of every measurement of the selected shape
+    pub fn tolerance_panel(&mut self, ui: &mut Ui) {
+        let Some(selection_index) = self.selected_shape_index else {
+            return;
+        };
+        let measurements = measure_shape(self.shapes[selection_index].as_ref(), &self.calibration);
+        if measurements.is_empty() {
+            return;
+        }
+        let properties = self.shapes[selection_index].properties_mut();
+        ui.heading(format!("Tolerances of {}", properties.name));
+        egui::Grid::new("tolerance_grid").num_columns(5).show(ui, |ui| {
+            for header in ["", "Nominal", "Lower", "Upper", ""] {
+                ui.strong(header);
+            }
+            ui.end_row();
+
+            for measurement in &measurements {
+                let mut toleranced = measurement.tolerance.is_some();
+                let unit = &measurement.unit;
+                ui.checkbox(&mut toleranced, measurement.name)
+                    .on_hover_text(format!("Measured {:.3} {unit}", measurement.value));
+                if toleranced != measurement.tolerance.is_some() {
+                    if toleranced {
+                        properties.tolerances.insert(measurement.name.to_string(), Tolerance::new(measurement.value, 0.));
+                    } else {
+                        properties.tolerances.remove(measurement.name);
+                    }
+                }
+
+                match properties.tolerances.get_mut(measurement.name) {
+                    Some(tolerance) => {
+                        ui.add(DragValue::new(&mut tolerance.nominal).speed(0.01).suffix(format!(" {unit}")));
+                        ui.add(DragValue::new(&mut tolerance.lower).clamp_range(f64::MIN..=0.).speed(0.01));
+                        ui.add(DragValue::new(&mut tolerance.upper).clamp_range(0.0..=f64::MAX).speed(0.01).prefix("+"));
+                    }
+                    None => {
+                        ui.weak(format!("{:.3} {unit}", measurement.value));
+                        ui.label("");
+                        ui.label("");
+                    }
+                }
+                status(ui, measurement);
+                ui.end_row();
+            }
+        });
+        ui.separator();
+    }
+
+    /// Every measurement out of its tolerance, or every toleranced one
+    pub fn tolerance_report_window(&mut self, ctx: &Context) {
+        let measurements: Vec<Measurement> = measure(&self.shapes, &self.calibration)
+            .into_iter()
+            .filter(|measurement| measurement.tolerance.is_some())
+            .collect();
+        let failed = measurements.iter().filter(|measurement| measurement.passed() == Some(false)).count();
+        egui::Window::new("Tolerance report")
+            .open(&mut self.show_tolerance_report)
+            .show(ctx, |ui| {
+                ui.label(format!("{failed} out of {} toleranced measurements are out of tolerance", measurements.len()));
+                ui.checkbox(&mut self.report_only_failed, "Only out of tolerance");
+                ui.separator();
+
+                let rows: Vec<&Measurement> = measurements.iter()
+                    .filter(|measurement| !self.report_only_failed || measurement.passed() == Some(false))
+                    .collect();
+                egui::ScrollArea::vertical().max_height(300.).show(ui, |ui| {
+                    egui::Grid::new("tolerance_report").striped(true).num_columns(7).show(ui, |ui| {
+                        for header in ["Shape", "Measurement", "Value", "Nominal", "Min", "Max", "Status"] {
+                            ui.strong(header);
+                        }
+                        ui.end_row();
+                        for &measurement in &rows {
+                            let Some(tolerance) = measurement.tolerance else {
+                                continue;
+                            };
+                            let unit = &measurement.unit;
+                            ui.label(&measurement.shape_name);
+                            ui.label(measurement.name);
+                            ui.label(format!("{:.3} {unit}", measurement.value));
+                            ui.label(format!("{:.3}", tolerance.nominal));
+                            ui.label(format!("{:.3}", tolerance.min()));
+                            ui.label(format!("{:.3}", tolerance.max()));
+                            status(ui, measurement);
+                            ui.end_row();
+                        }
+                    });
+                });
+
+                if ui.button("Copy report").clicked() {
+                    let mut text = String::from("shape\tmeasurement\tvalue\tnominal\tmin\tmax\tunit\tstatus\n");
+                    for measurement in &rows {
+                        let Some(tolerance) = measurement.tolerance else {
+                            continue;
+                        };
+                        let _ = writeln!(
+                            text,
+                            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                            measurement.shape_name,
+                            measurement.name,
+                            measurement.value,
+                            tolerance.nominal,
+                            tolerance.min(),
+                            tolerance.max(),
+                            measurement.unit,
+                            if measurement.passed() == Some(true) { "PASS" } else { "FAIL" },
+                        );
+                    }
+                    ui.ctx().output_mut(|output| output.copied_text = text);
+                }
+            });
+    }
+}
@@ -1,31 +1,65 @@
-use super::BlueKompassApp;
+use super::{BlueKompassApp, Dragging};
 
-use egui_plot::{PlotUi, PlotPoint};
+use egui_plot::PlotUi;
+
+use bluekompass_core::point::{Point, Vec2};
+use bluekompass_core::shapes::build_shape;
+use crate::render::to_point;
 
 impl BlueKompassApp {
-    fn update_shape(&mut self, shape_index: usize, pos: PlotPoint) {
-        self.select_point_from_shape(shape_index, pos);
-        if let Some(point_index) = self.selected_point_index {
-            let shape = &mut self.shapes[shape_index];
-            shape.replace(point_index, pos);
+    fn start_drag(&mut self, pos: Point) {
+        // Grip points have priority over the body of shapes
+        self.dragging = if let Some((shape_index, point_index)) = self.find_grip_point(pos) {
+            Some(Dragging::Point { shape_index, point_index })
+        } else {
+            self.find_shape(pos)
+                .filter(|&shape_index| !self.is_locked(shape_index))
+                .map(|shape_index| Dragging::Shape { shape_index })
+        };
+
+        if let Some(Dragging::Point { shape_index, .. } | Dragging::Shape { shape_index }) = self.dragging {
+            self.unselect_shape();
+            self.select_shape(shape_index);
         }
     }
 
-    pub fn move_selected_point(&mut self, plot_ui: &mut PlotUi) -> bool {
-        if let Some(selected_index) = self.selected_shape_index {
-            let response = plot_ui.response();
-            if plot_ui.ctx().input(|i| i.pointer.primary_down()) {
-                match plot_ui.pointer_coordinate() {
-                    Some(pos) if response.contains_pointer() => {
-                        self.update_shape(selected_index, pos);
-                        return true;
+    pub fn drag_selection(&mut self, plot_ui: &mut PlotUi) -> bool {
+        let response = plot_ui.response();
+        if response.drag_started() {
+            // The drag starts once the pointer has moved, so use the position where it was pressed
+            let origin = plot_ui.ctx().input(|i| i.pointer.press_origin());
+            if let Some(origin) = origin {
+                self.start_drag(to_point(plot_ui.transform().value_from_position(origin)));
+            }
+        }
+
+        let dragging = self.dragging;
+        if response.drag_stopped() {
+            self.dragging = None;
+        }
+
+        match dragging {
+            Some(Dragging::Point { shape_index, point_index }) => {
+                if let Some(pos) = plot_ui.pointer_coordinate() {
+                    let shape = &mut self.shapes[shape_index];
+                    if !shape.try_replace(point_index, to_point(pos)) {
+                        // Keep the shape valid and preview the degenerate one
+                        let mut points = shape.as_slice().to_vec();
+                        points[point_index] = to_point(pos);
+                        self.invalid_preview = build_shape(shape.kind(), &points).map(|mut preview| {
+                            *preview.properties_mut() = shape.properties().clone();
+                            preview
+                        });
                     }
-                    _ => (),
                 }
-            } else {
-                self.selected_point_index = None;
+                true
+            }
+            Some(Dragging::Shape { shape_index }) => {
+                let delta = plot_ui.pointer_coordinate_drag_delta();
+                self.shapes[shape_index].translate(Vec2::new(delta.x as f64, delta.y as f64));
+                true
             }
+            None => false,
         }
-        false
     }
 }
@@ -1,5 +1,8 @@
-use egui_plot::{PlotPoint, PlotUi};
-use crate::shapes::Shape;
+use egui_plot::PlotUi;
+use bluekompass_core::layers::Layer;
+use bluekompass_core::point::Point;
+use bluekompass_core::shapes::Shape;
+use bluekompass_core::style::Style;
 
 mod line;
 mod circle;
@@ -8,12 +11,12 @@ pub use line::Line;
 pub use circle::Circle;
 
 pub trait ShapeBuilder {
-    fn build(&self, points: &Vec<PlotPoint>) -> Option<Box<dyn Shape>>;
-    fn draw(&self, points: &Vec<PlotPoint>, plot_ui: &mut PlotUi, current_point: PlotPoint);
+    fn build(&self, points: &[Point]) -> Option<Box<dyn Shape>>;
+    fn draw(&self, points: &[Point], plot_ui: &mut PlotUi, current_point: Point, layer: &Layer, style: &Style);
 }
 
 pub struct Builder {
-    points: Vec<PlotPoint>,
+    points: Vec<Point>,
 }
 
 impl Builder {
@@ -27,14 +30,23 @@ impl Builder {
         self.points.clear();
     }
 
-    pub fn set_next_point(&mut self, point: PlotPoint) {
+    /// Number of points collected for the shape being built
+    pub fn point_count(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn undo_last_point(&mut self) {
+        self.points.pop();
+    }
+
+    pub fn set_next_point(&mut self, point: Point) {
         if self.points.len() < 3 {
             self.points.push(point);
         }
     }
 
-    pub fn draw<T: ShapeBuilder>(&self, plot_ui: &mut PlotUi, current_point: PlotPoint, shape: T) { 
-        shape.draw(&self.points, plot_ui, current_point);
+    pub fn draw<T: ShapeBuilder>(&self, plot_ui: &mut PlotUi, current_point: Point, layer: &Layer, style: &Style, shape: T) { 
+        shape.draw(&self.points, plot_ui, current_point, layer, style);
     }
 
     pub fn build<T: ShapeBuilder>(&self, shape: T) -> Option<Box<dyn Shape>> {
@@ -1,13 +1,16 @@
-use egui_plot::{MarkerShape, PlotPoints, PlotPoint, PlotUi};
-use eframe::epaint;
+use egui_plot::PlotUi;
 
-use crate::shapes::{self, Shape, Draw};
+use bluekompass_core::layers::Layer;
+use bluekompass_core::point::Point;
+use bluekompass_core::shapes::{self, Shape};
+use bluekompass_core::style::Style;
+use crate::render::{Draw, draw_grips, to_color32};
 use super::ShapeBuilder;
 
 pub struct Circle;
 
 impl ShapeBuilder for Circle {  
-    fn build(&self, points: &Vec<PlotPoint>) -> Option<Box<dyn Shape>> {
+    fn build(&self, points: &[Point]) -> Option<Box<dyn Shape>> {
         if points.len() < 3 {
             return None;
         }
@@ -15,28 +18,15 @@ impl ShapeBuilder for Circle {
     }
 
 
-    fn draw(&self, points: &Vec<PlotPoint>, plot_ui: &mut PlotUi, current_point: PlotPoint) {
-        if points.len() > 0 {
+    fn draw(&self, points: &[Point], plot_ui: &mut PlotUi, current_point: Point, layer: &Layer, style: &Style) {
+        if !points.is_empty() {
             if points.len() > 1 {
-                let circle = shapes::Circle::new([points[0], points[1], current_point]);
-                circle.draw(plot_ui);
+                let mut circle: Box<dyn Shape> = Box::new(shapes::Circle::new([points[0], points[1], current_point]));
+                circle.properties_mut().style = style.clone();
+                circle.draw(plot_ui, layer);
             } else {
-                plot_ui.points(
-                    egui_plot::Points::new(PlotPoints::Owned(points.to_vec()))
-                        .radius(6.0)
-                        .filled(true)
-                        .shape(MarkerShape::Circle)
-                        .color(epaint::Color32::WHITE)
-                );
-                plot_ui.points(
-                    egui_plot::Points::new(PlotPoints::Owned(points.to_vec()))
-                        .radius(5.0)
-                        .filled(true)
-                        .shape(MarkerShape::Circle)
-                        .color(epaint::Color32::BLACK)
-                );
+                draw_grips(plot_ui, points, to_color32(style.color(layer)), style.marker_size);
             }
         }
     }
 }
-
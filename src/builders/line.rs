@@ -1,12 +1,16 @@
-use egui_plot::{PlotPoint, PlotUi};
+use egui_plot::PlotUi;
 
-use crate::shapes::{self, Shape, Draw};
+use bluekompass_core::layers::Layer;
+use bluekompass_core::point::Point;
+use bluekompass_core::shapes::{self, Shape};
+use bluekompass_core::style::Style;
+use crate::render::Draw;
 use super::ShapeBuilder;
 
 pub struct Line;
 
 impl ShapeBuilder for Line { 
-    fn build(&self, points: &Vec<PlotPoint>) -> Option<Box<dyn Shape>> {
+    fn build(&self, points: &[Point]) -> Option<Box<dyn Shape>> {
         if points.len() < 2 {
             return None;
         }
@@ -14,11 +18,11 @@ impl ShapeBuilder for Line {
     }
 
 
-    fn draw(&self, points: &Vec<PlotPoint>, plot_ui: &mut PlotUi, current_point: PlotPoint) {
-        if points.len() > 0 {
-            let line = shapes::Line::new([points[0], current_point]);
-            line.draw(plot_ui);
+    fn draw(&self, points: &[Point], plot_ui: &mut PlotUi, current_point: Point, layer: &Layer, style: &Style) {
+        if !points.is_empty() {
+            let mut line: Box<dyn Shape> = Box::new(shapes::Line::new([points[0], current_point]));
+            line.properties_mut().style = style.clone();
+            line.draw(plot_ui, layer);
         }
     }
 }
-
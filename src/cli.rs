@@ -0,0 +1,431 @@
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use bluekompass_core::calibration::Resolution;
+use bluekompass_core::export::delimited_field;
+use bluekompass_core::measure::{measure, Measurement};
+use bluekompass_core::project::Project;
+
+use crate::app::{Mode, StartupOptions};
+use crate::image_loader::{image_resolution, image_size, oriented};
+use crate::raster::{self, RasterOptions};
+
+const GUI_USAGE: &str = "\
+Usage: bluekompass [<image|project.bkp>] [--mode <mode>] [--zoom-to-fit]
+       bluekompass measure --help
+       bluekompass render --help
+
+Open BlueKompass, with the image or project already loaded when given.
+
+Options:
+  -m, --mode <mode>  Initial mode: drag, selection, line or circle
+  -f, --zoom-to-fit  Fit the view to the image at startup";
+
+const USAGE: &str = "\
+Usage: bluekompass measure <project.bkp> [--image <image>]... [--out <results.csv>]
+
+Measure the shapes of a project without opening a window.
+
+Options:
+  -i, --image <image>  Image to measure, can be repeated (default: image of the project)
+  -o, --out <file>     Write the measurements as CSV in a file instead of the standard output
+
+Measurements are in the calibrated units of the project, angles in degrees. An uncalibrated project is
+measured in millimeters on images whose files give their resolution.
+Exit status is 0 when every measurement is within its tolerance, 1 when at least one is not
+and 2 on errors.";
+
+const RENDER_USAGE: &str = "\
+Usage: bluekompass render <project.bkp> --out <image.png|image.jpg> [--image <image>] [--scale <factor>] [--no-labels]
+
+Render an image with the shapes of a project burned in, without opening a window.
+
+Options:
+  -o, --out <file>      Rendered image, PNG or JPEG depending on the extension
+  -i, --image <image>   Image to render on (default: image of the project)
+  -s, --scale <factor>  Size relative to the native resolution of the image (default: 1)
+      --no-labels       Do not draw the labels of the shapes";
+
+pub const EXIT_FAILED_TOLERANCE: i32 = 1;
+pub const EXIT_ERROR: i32 = 2;
+
+fn parse_gui_args(args: &[String]) -> Result<StartupOptions, String> {
+    let mut options = StartupOptions::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-m" | "--mode" => {
+                let name = args.next().ok_or("missing value for --mode")?;
+                let mode = Mode::from_name(name)
+                    .ok_or_else(|| format!("unknown mode {name}, expected one of {}", Mode::NAMES.join(", ")))?;
+                options.mode = Some(mode);
+            }
+            "-f" | "--zoom-to-fit" => options.zoom_to_fit = true,
+            "-h" | "--help" => return Err(String::new()),
+            option if option.starts_with('-') => return Err(format!("unknown option {option}")),
+            path if options.file.is_none() => options.file = Some(path.into()),
+            path => return Err(format!("unexpected argument {path}")),
+        }
+    }
+    Ok(options)
+}
+
+/// Print the usage after a parsing error and return the exit status, an empty error is an explicit `--help`
+/// printed on the standard output
+fn usage_status(error: &str, usage: &str) -> i32 {
+    if error.is_empty() {
+        println!("{usage}");
+        return 0;
+    }
+    eprintln!("error: {error}\n\n{usage}");
+    EXIT_ERROR
+}
+
+/// Parse the options of the graphical application, on error return the exit status
+pub fn startup_options(args: &[String]) -> Result<StartupOptions, i32> {
+    parse_gui_args(args).map_err(|error| usage_status(&error, GUI_USAGE))
+}
+
+struct MeasureArgs {
+    project: PathBuf,
+    images: Vec<PathBuf>,
+    out: Option<PathBuf>,
+}
+
+fn parse_measure_args(args: &[String]) -> Result<MeasureArgs, String> {
+    let mut project = None;
+    let mut images = Vec::new();
+    let mut out = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-i" | "--image" => {
+                images.push(args.next().ok_or("missing value for --image")?.into());
+            }
+            "-o" | "--out" => {
+                out = Some(args.next().ok_or("missing value for --out")?.into());
+            }
+            "-h" | "--help" => return Err(String::new()),
+            option if option.starts_with('-') => return Err(format!("unknown option {option}")),
+            path if project.is_none() => project = Some(path.into()),
+            path => return Err(format!("unexpected argument {path}")),
+        }
+    }
+    let project = project.ok_or("missing project file")?;
+    Ok(MeasureArgs { project, images, out })
+}
+
+/// Resolution of an image, with a warning when the project would be calibrated by it but its pixels are not square
+fn resolution(image: &Path, project: &Project) -> Option<Resolution> {
+    let resolution = image_resolution(image)?;
+    if !project.calibration.is_calibrated() && resolution.pixel_size().is_some() && !resolution.is_square() {
+        eprintln!("warning: the pixels of {} are not square, its resolution is not used as calibration", image.display());
+    }
+    Some(resolution)
+}
+
+/// Measure every image and return whether all measurements are within their tolerance
+///
+/// Each image is measured with the shapes following the operations of the project on an image of its size, and
+/// with its resolution when the project is not calibrated.
+fn run_measure(args: &MeasureArgs) -> Result<bool, String> {
+    let mut project = Project::load(&args.project)
+        .map_err(|error| format!("{}: {error}", args.project.display()))?;
+    let project_size = project.image.as_deref().and_then(image_size);
+    if !project.upgrade(project_size) {
+        eprintln!("warning: the image of this older project is missing, its shapes are not placed on the image");
+    }
+
+    let images = if args.images.is_empty() {
+        project.image.iter().cloned().collect()
+    } else {
+        args.images.clone()
+    };
+    let mut rows: Vec<(String, Option<[usize; 2]>, Vec<Measurement>)> = images.iter()
+        .map(|image| {
+            let size = image_size(image)
+                .ok_or_else(|| format!("{}: cannot read the size of the image", image.display()))?;
+            if project_size.is_some_and(|project_size| project_size != size) {
+                eprintln!("warning: {} does not have the size of the image of the project", image.display());
+            }
+            let (shapes, calibration) = project.on_image(project_size, size, resolution(image, &project));
+            Ok((image.display().to_string(), Some(size), measure(&shapes, &calibration)))
+        })
+        .collect::<Result<_, String>>()?;
+    if rows.is_empty() {
+        // Measure the project alone when it has no image
+        rows.push((String::new(), None, measure(&project.shapes(), &project.calibration)));
+    }
+
+    let mut csv = String::from("image,width,height,shape_id,shape,type,measurement,value,nominal,min,max,status,unit\n");
+    let mut failed = 0;
+    for (image, size, measurements) in &rows {
+        let (width, height) = size.map_or((String::new(), String::new()), |[w, h]| (w.to_string(), h.to_string()));
+        for measurement in measurements {
+            let (nominal, min, max) = measurement.tolerance.map_or(
+                (String::new(), String::new(), String::new()),
+                |tolerance| (tolerance.nominal.to_string(), tolerance.min().to_string(), tolerance.max().to_string()),
+            );
+            let status = match measurement.passed() {
+                Some(true) => "PASS",
+                Some(false) => {
+                    failed += 1;
+                    "FAIL"
+                }
+                None => "",
+            };
+            let _ = writeln!(
+                csv,
+                "{},{width},{height},{},{},{},{},{},{nominal},{min},{max},{status},{}",
+                delimited_field(image, ','),
+                measurement.shape_id,
+                delimited_field(&measurement.shape_name, ','),
+                measurement.kind.name(),
+                measurement.name,
+                measurement.value,
+                delimited_field(&measurement.unit, ','),
+            );
+        }
+    }
+
+    match &args.out {
+        Some(out) => fs::write(out, csv).map_err(|error| format!("{}: {error}", out.display()))?,
+        None => print!("{csv}"),
+    }
+    for (image, _, measurements) in &rows {
+        for measurement in measurements.iter().filter(|measurement| measurement.passed() == Some(false)) {
+            if let Some(tolerance) = measurement.tolerance {
+                let image = if image.is_empty() { String::new() } else { format!("{image}: ") };
+                eprintln!(
+                    "FAIL {image}{} {}: {} {unit} not in {} .. {} {unit}",
+                    measurement.shape_name,
+                    measurement.name,
+                    measurement.value,
+                    tolerance.min(),
+                    tolerance.max(),
+                    unit = measurement.unit,
+                );
+            }
+        }
+    }
+    eprintln!(
+        "{} measurements on {} image(s), {failed} out of tolerance",
+        rows.iter().map(|(_, _, measurements)| measurements.len()).sum::<usize>(),
+        images.len(),
+    );
+    Ok(failed == 0)
+}
+
+/// Run the `measure` command and return the exit status
+pub fn measure_command(args: &[String]) -> i32 {
+    let args = match parse_measure_args(args) {
+        Ok(args) => args,
+        Err(error) => return usage_status(&error, USAGE),
+    };
+    match run_measure(&args) {
+        Ok(true) => 0,
+        Ok(false) => EXIT_FAILED_TOLERANCE,
+        Err(error) => {
+            eprintln!("error: {error}");
+            EXIT_ERROR
+        }
+    }
+}
+
+struct RenderArgs {
+    project: PathBuf,
+    image: Option<PathBuf>,
+    out: PathBuf,
+    options: RasterOptions,
+}
+
+fn parse_render_args(args: &[String]) -> Result<RenderArgs, String> {
+    let mut project = None;
+    let mut image = None;
+    let mut out = None;
+    let mut options = RasterOptions::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-i" | "--image" => {
+                image = Some(args.next().ok_or("missing value for --image")?.into());
+            }
+            "-o" | "--out" => {
+                out = Some(args.next().ok_or("missing value for --out")?.into());
+            }
+            "-s" | "--scale" => {
+                let scale = args.next().ok_or("missing value for --scale")?;
+                options.scale = scale.parse()
+                    .ok()
+                    .filter(|scale: &f32| scale.is_finite() && *scale > 0.)
+                    .ok_or_else(|| format!("invalid scale {scale}"))?;
+            }
+            "--no-labels" => options.labels = false,
+            "-h" | "--help" => return Err(String::new()),
+            option if option.starts_with('-') => return Err(format!("unknown option {option}")),
+            path if project.is_none() => project = Some(path.into()),
+            path => return Err(format!("unexpected argument {path}")),
+        }
+    }
+    let project = project.ok_or("missing project file")?;
+    let out = out.ok_or("missing output file")?;
+    Ok(RenderArgs { project, image, out, options })
+}
+
+/// Render the image with the shapes placed and labelled as `measure` measures them
+fn run_render(args: &RenderArgs) -> Result<(), String> {
+    let mut project = Project::load(&args.project)
+        .map_err(|error| format!("{}: {error}", args.project.display()))?;
+    let project_size = project.image.as_deref().and_then(image_size);
+    if !project.upgrade(project_size) {
+        eprintln!("warning: the image of this older project is missing, its shapes are not placed on the image");
+    }
+    let image = args.image.as_ref()
+        .or(project.image.as_ref())
+        .ok_or("the project has no image, give one with --image")?;
+    let pixels = image::open(image)
+        .map_err(|error| format!("{}: {error}", image.display()))?
+        .to_rgba8();
+    let size = [pixels.width() as usize, pixels.height() as usize];
+    if project_size.is_some_and(|project_size| project_size != size) {
+        eprintln!("warning: {} does not have the size of the image of the project", image.display());
+    }
+    let (shapes, calibration) = project.on_image(project_size, size, resolution(image, &project));
+    let pixels = oriented(&pixels, &project.image_operations);
+    let rendered = raster::render(&pixels, &shapes, &project.layers, &calibration, &args.options)
+        .map_err(|error| error.to_string())?;
+    raster::save(rendered, &args.out).map_err(|error| format!("{}: {error}", args.out.display()))
+}
+
+/// Run the `render` command and return the exit status
+pub fn render_command(args: &[String]) -> i32 {
+    let args = match parse_render_args(args) {
+        Ok(args) => args,
+        Err(error) => return usage_status(&error, RENDER_USAGE),
+    };
+    match run_render(&args) {
+        Ok(()) => 0,
+        Err(error) => {
+            eprintln!("error: {error}");
+            EXIT_ERROR
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::path::Path;
+
+    use bluekompass_core::layers::Layer;
+    use bluekompass_core::point::Point;
+    use bluekompass_core::shapes::{Circle, Line, Shape};
+    use bluekompass_core::style::Style;
+    use bluekompass_core::tolerance::Tolerance;
+    use image::RgbaImage;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    /// Empty directory of a test, in the temporary directory
+    fn directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("bluekompass-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    /// Project of a 100 × 50 image with a line of 50 px and a circle of radius 10 px, measured against tolerances
+    fn save_project(directory: &Path, radius: f64) -> PathBuf {
+        let image = directory.join("part.png");
+        RgbaImage::new(100, 50).save(&image).unwrap();
+        let mut line = Line::new([Point::new(10., -5.), Point::new(40., -45.)]);
+        line.properties_mut().name = "Edge, top".to_string();
+        line.properties_mut().tolerances.insert("Length".to_string(), Tolerance::new(50., 0.5));
+        let mut circle = Circle::new([Point::new(80., -25.), Point::new(70., -15.), Point::new(60., -25.)]);
+        circle.properties_mut().tolerances.insert("Radius".to_string(), Tolerance::new(radius, 0.5));
+        let shapes: Vec<Box<dyn Shape>> = vec![Box::new(line), Box::new(circle)];
+        let path = directory.join("part.bkp");
+        Project::new(Some(image), &[Layer::default()], &Style::default(), &shapes).save(&path).unwrap();
+        path
+    }
+
+    #[test]
+    fn gui_arguments_give_the_startup_options() {
+        let options = parse_gui_args(&args(&["part.png", "--mode", "Line", "-f"])).unwrap();
+        assert_eq!(options.file, Some(PathBuf::from("part.png")));
+        assert!(options.mode == Some(Mode::LINE));
+        assert!(options.zoom_to_fit);
+
+        assert_eq!(parse_gui_args(&args(&["--help"])).err().as_deref(), Some(""));
+        assert!(parse_gui_args(&args(&["--mode", "spline"])).err().unwrap().starts_with("unknown mode spline"));
+        assert_eq!(parse_gui_args(&args(&["-m"])).err().as_deref(), Some("missing value for --mode"));
+        assert_eq!(parse_gui_args(&args(&["a.png", "b.png"])).err().as_deref(), Some("unexpected argument b.png"));
+        assert_eq!(startup_options(&args(&["-h"])).err(), Some(0));
+        assert_eq!(startup_options(&args(&["--zoom"])).err(), Some(EXIT_ERROR));
+    }
+
+    #[test]
+    fn measure_arguments_repeat_the_images() {
+        let parsed = parse_measure_args(&args(&["part.bkp", "-i", "a.png", "--image", "b.png", "-o", "out.csv"])).unwrap();
+        assert_eq!(parsed.project, PathBuf::from("part.bkp"));
+        assert_eq!(parsed.images, [PathBuf::from("a.png"), PathBuf::from("b.png")]);
+        assert_eq!(parsed.out, Some(PathBuf::from("out.csv")));
+
+        assert_eq!(parse_measure_args(&args(&["--help"])).err().as_deref(), Some(""));
+        assert_eq!(parse_measure_args(&args(&[])).err().as_deref(), Some("missing project file"));
+        assert_eq!(parse_measure_args(&args(&["part.bkp", "--out"])).err().as_deref(), Some("missing value for --out"));
+        assert_eq!(parse_measure_args(&args(&["part.bkp", "-x"])).err().as_deref(), Some("unknown option -x"));
+        assert_eq!(measure_command(&args(&["--help"])), 0);
+        assert_eq!(measure_command(&args(&["a.bkp", "b.bkp"])), EXIT_ERROR);
+    }
+
+    #[test]
+    fn render_arguments_need_an_output() {
+        let parsed = parse_render_args(&args(&["part.bkp", "-o", "out.png", "-i", "other.png", "-s", "0.5", "--no-labels"]))
+            .unwrap();
+        assert_eq!(parsed.project, PathBuf::from("part.bkp"));
+        assert_eq!(parsed.out, PathBuf::from("out.png"));
+        assert_eq!(parsed.image, Some(PathBuf::from("other.png")));
+        assert_eq!(parsed.options.scale, 0.5);
+        assert!(!parsed.options.labels);
+
+        assert_eq!(parse_render_args(&args(&["part.bkp"])).err().as_deref(), Some("missing output file"));
+        for scale in ["0", "-1", "NaN", "big"] {
+            let error = parse_render_args(&args(&["part.bkp", "-o", "out.png", "--scale", scale])).err();
+            assert_eq!(error, Some(format!("invalid scale {scale}")));
+        }
+        assert_eq!(render_command(&args(&["-h"])), 0);
+        assert_eq!(render_command(&args(&["part.bkp"])), EXIT_ERROR);
+    }
+
+    #[test]
+    fn measurements_are_written_as_csv_with_their_status() {
+        let directory = directory("measure");
+        let out = directory.join("results.csv");
+        let project = save_project(&directory, 12.);
+        let measure_args = MeasureArgs { project: project.clone(), images: Vec::new(), out: Some(out.clone()) };
+
+        // The circle is out of tolerance
+        assert_eq!(run_measure(&measure_args), Ok(false));
+        let csv = fs::read_to_string(&out).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "image,width,height,shape_id,shape,type,measurement,value,nominal,min,max,status,unit");
+        let image = directory.join("part.png").display().to_string();
+        assert!(lines[1].starts_with(&format!("{image},100,50,")), "{}", lines[1]);
+        assert!(lines[1].contains(r#","Edge, top",Line,Length,50,50,49.5,50.5,PASS,px"#), "{}", lines[1]);
+        assert!(lines.iter().any(|line| line.contains(",Circle,Radius,10,12,11.5,12.5,FAIL,px")), "{csv}");
+        assert_eq!(measure_command(&args(&[project.to_str().unwrap(), "-o", out.to_str().unwrap()])), EXIT_FAILED_TOLERANCE);
+
+        save_project(&directory, 10.);
+        assert_eq!(run_measure(&measure_args), Ok(true));
+        assert_eq!(measure_command(&args(&[project.to_str().unwrap(), "-o", out.to_str().unwrap()])), 0);
+
+        let missing = MeasureArgs { images: vec![directory.join("missing.png")], ..measure_args };
+        assert!(run_measure(&missing).unwrap_err().contains("missing.png"));
+        let _ = fs::remove_dir_all(&directory);
+    }
+}
@@ -1,36 +1,302 @@
 use eframe::egui::{TextureId, TextureOptions, ColorImage, TextureHandle, Ui};
+use std::fs::File;
+use std::io::BufReader;
 use std::path::{Path, PathBuf};
-use image::ImageError;
+use image::{DynamicImage, ExtendedColorType, ImageDecoder, ImageError, ImageFormat, RgbaImage};
 
-fn load_image_from_path(path: &Path) -> Result<ColorImage, ImageError> {
-    let image = image::io::Reader::open(path)?.decode()?;
-    let size = [image.width() as _, image.height() as _];
-    let image_buffer = image.to_rgba8();
-    let pixels = image_buffer.as_flat_samples();
-    Ok(ColorImage::from_rgba_unmultiplied(size, pixels.as_slice()))
+use bluekompass_core::adjustments::Adjustments;
+use bluekompass_core::calibration::{Resolution, ResolutionUnit};
+use bluekompass_core::orientation::ImageOperation;
+
+/// What the file of an image tells besides its pixels
+#[derive(Debug, Clone)]
+pub struct ImageInfo {
+    pub format: Option<ImageFormat>,
+    pub size: [usize; 2],
+    /// Color type of the pixels as stored
+    pub color_type: ExtendedColorType,
+    /// Physical resolution, from the pHYs chunk of PNG files or the resolution tags of TIFF files
+    pub resolution: Option<Resolution>,
+}
+
+impl ImageInfo {
+    pub fn color_name(&self) -> &'static str {
+        match self.color_type.channel_count() {
+            1 => "Grayscale",
+            2 => "Grayscale with alpha",
+            3 => "RGB",
+            4 => "RGBA",
+            _ => "Other",
+        }
+    }
+
+    pub fn bit_depth(&self) -> u16 {
+        self.color_type.bits_per_pixel() / u16::from(self.color_type.channel_count().max(1))
+    }
+}
+
+/// Resolution in pixels per meter, PNG files without unit only give the aspect ratio of their pixels
+fn png_resolution(path: &Path) -> Option<Resolution> {
+    let reader = png::Decoder::new(BufReader::new(File::open(path).ok()?)).read_info().ok()?;
+    let dimensions = reader.info().pixel_dims?;
+    (dimensions.unit == png::Unit::Meter).then_some(Resolution {
+        x: dimensions.xppu as f64,
+        y: dimensions.yppu as f64,
+        unit: ResolutionUnit::Meter,
+    })
+}
+
+/// Resolution from the XResolution, YResolution and ResolutionUnit tags, inches when the unit is missing
+fn tiff_resolution(path: &Path) -> Option<Resolution> {
+    use tiff::tags::Tag;
+    let mut decoder = tiff::decoder::Decoder::new(BufReader::new(File::open(path).ok()?)).ok()?;
+    let mut rational = |tag| -> Option<f64> {
+        match decoder.find_tag(tag).ok()??.into_u64_vec().ok()?[..] {
+            [numerator, denominator] => Some(numerator as f64 / denominator as f64),
+            _ => None,
+        }
+    };
+    let (x, y) = (rational(Tag::XResolution)?, rational(Tag::YResolution)?);
+    let unit = match decoder.find_tag(Tag::ResolutionUnit).ok()?.map(|unit| unit.into_u16()) {
+        None => ResolutionUnit::Inch,
+        Some(Ok(2)) => ResolutionUnit::Inch,
+        Some(Ok(3)) => ResolutionUnit::Centimeter,
+        _ => return None,
+    };
+    Some(Resolution { x, y, unit })
+}
+
+/// Physical resolution of an image, read from the metadata of PNG and TIFF files
+pub fn image_resolution(path: &Path) -> Option<Resolution> {
+    match ImageFormat::from_path(path).ok()? {
+        ImageFormat::Png => png_resolution(path),
+        ImageFormat::Tiff => tiff_resolution(path),
+        _ => None,
+    }
+}
+
+/// Decoded pixels of an image, with what its file tells about them
+pub fn load_image_from_path(path: &Path) -> Result<(RgbaImage, ImageInfo), ImageError> {
+    let reader = image::ImageReader::open(path)?;
+    let format = reader.format();
+    let decoder = reader.into_decoder()?;
+    let color_type = decoder.original_color_type();
+    let pixels = DynamicImage::from_decoder(decoder)?.to_rgba8();
+    let resolution = image_resolution(path);
+    let size = [pixels.width() as usize, pixels.height() as usize];
+    Ok((pixels, ImageInfo { format, size, color_type, resolution }))
+}
+
+/// Width and height of an image, read from its header only
+pub fn image_size(path: &Path) -> Option<[usize; 2]> {
+    image::image_dimensions(path).ok().map(|(width, height)| [width as usize, height as usize])
+}
+
+/// Pixels rotated and flipped by the operations, in order
+pub fn oriented(pixels: &RgbaImage, operations: &[ImageOperation]) -> RgbaImage {
+    let mut size = [pixels.width() as usize, pixels.height() as usize];
+    let mut rgba = pixels.as_raw().clone();
+    for operation in operations {
+        (size, rgba) = operation.apply(size, &rgba);
+    }
+    RgbaImage::from_raw(size[0] as u32, size[1] as u32, rgba).expect("operations keep one RGBA value per pixel")
+}
+
+/// Texture of the pixels once adjusted, the pixels themselves are left untouched
+fn adjusted_image(pixels: &RgbaImage, adjustments: &Adjustments) -> ColorImage {
+    let size = [pixels.width() as _, pixels.height() as _];
+    let mut rgba = pixels.as_raw().clone();
+    adjustments.apply(&mut rgba);
+    ColorImage::from_rgba_unmultiplied(size, &rgba)
 }
 
 pub struct BlueKompassImage {
     image_path: Box<Path>,
+    /// Decoded pixels, as in the file
+    original: Option<RgbaImage>,
+    info: Option<ImageInfo>,
+    /// Decoded pixels once rotated and flipped, kept on the CPU to adjust the texture
+    pixels: Option<RgbaImage>,
     texture: Option<TextureHandle>,
+    /// Adjustments applied to the texture
+    adjustments: Adjustments,
+    /// Operations applied to the pixels
+    operations: Vec<ImageOperation>,
 }
 
 impl BlueKompassImage {
-    pub fn new(image_path: PathBuf) -> Self {
-        Self { texture: None, image_path: image_path.into() }
+    fn new(image_path: PathBuf) -> Self {
+        Self {
+            texture: None,
+            original: None,
+            info: None,
+            pixels: None,
+            image_path: image_path.into(),
+            adjustments: Adjustments::default(),
+            operations: Vec::new(),
+        }
+    }
+
+    /// Image decoded from its file, so that a file that cannot be decoded is refused when it is opened
+    pub fn open(image_path: PathBuf) -> Result<Self, ImageError> {
+        let mut image = Self::new(image_path);
+        image.decode()?;
+        Ok(image)
+    }
+
+    fn decode(&mut self) -> Result<(), ImageError> {
+        if self.original.is_none() {
+            let (original, info) = load_image_from_path(&self.image_path)?;
+            self.original = Some(original);
+            self.info = Some(info);
+        }
+        Ok(())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.image_path
+    }
+
+    /// Size of the image once its texture is loaded
+    pub fn size(&self) -> Option<[usize; 2]> {
+        self.texture.as_ref().map(TextureHandle::size)
+    }
+
+    /// What the file tells about the image, once it is loaded
+    pub fn info(&self) -> Option<&ImageInfo> {
+        self.info.as_ref()
+    }
+
+    /// Decoded pixels once rotated and flipped, without adjustments
+    pub fn pixels(&self) -> Option<&RgbaImage> {
+        self.pixels.as_ref()
     }
 }
 
 impl BlueKompassImage {
-    pub fn load(&mut self, ui: &mut Ui) -> (TextureId, [usize; 2]) {
-        let texture: &TextureHandle = self.texture.get_or_insert_with(|| {
-            // Load the texture only once.
-            ui.ctx().load_texture(
-                "my-image",
-                load_image_from_path(&self.image_path).unwrap(),
-                TextureOptions::NEAREST
-            )
-        });
-        (texture.id(), texture.size())
+    /// Texture of the image, the file is decoded the first time
+    pub fn load(
+        &mut self,
+        ui: &mut Ui,
+        adjustments: &Adjustments,
+        operations: &[ImageOperation],
+    ) -> Result<(TextureId, [usize; 2]), ImageError> {
+        self.decode()?;
+        let original = self.original.as_ref().expect("the image is loaded");
+        let reoriented = self.pixels.is_none() || self.operations != operations;
+        if reoriented {
+            self.pixels = Some(oriented(original, operations));
+            self.operations = operations.to_vec();
+        }
+        let pixels = self.pixels.as_ref().expect("the pixels are oriented");
+        match &mut self.texture {
+            // Upload the texture again only when the adjustments or the orientation change
+            Some(texture) if reoriented || self.adjustments != *adjustments => {
+                texture.set(adjusted_image(pixels, adjustments), TextureOptions::NEAREST);
+                self.adjustments = adjustments.clone();
+            }
+            Some(_) => (),
+            None => {
+                // Load the texture only once.
+                self.texture = Some(ui.ctx().load_texture(
+                    "my-image",
+                    adjusted_image(pixels, adjustments),
+                    TextureOptions::NEAREST
+                ));
+                self.adjustments = adjustments.clone();
+            }
+        }
+        let texture = self.texture.as_ref().expect("the texture is loaded");
+        Ok((texture.id(), texture.size()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+    use tiff::encoder::{colortype, Rational, TiffEncoder};
+
+    /// Empty directory of a test, in the temporary directory
+    fn directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("bluekompass-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    fn png(path: &Path, dimensions: Option<png::PixelDimensions>) {
+        let mut encoder = png::Encoder::new(File::create(path).unwrap(), 2, 2);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_pixel_dims(dimensions);
+        encoder.write_header().unwrap().write_image_data(&[0; 4]).unwrap();
+    }
+
+    /// TIFF file with the resolution tags, or with the ones the encoder writes by default
+    fn tiff(path: &Path, resolution: Option<(tiff::tags::ResolutionUnit, Rational, Rational)>) {
+        let mut encoder = TiffEncoder::new(File::create(path).unwrap()).unwrap();
+        let mut image = encoder.new_image::<colortype::Gray8>(2, 2).unwrap();
+        if let Some((unit, x, y)) = resolution {
+            image.resolution_unit(unit);
+            image.x_resolution(x);
+            image.y_resolution(y);
+        }
+        image.write_data(&[0; 4]).unwrap();
+    }
+
+    fn assert_resolution(resolution: Option<Resolution>, x: f64, y: f64, unit: ResolutionUnit) {
+        let resolution = resolution.expect("the file gives a resolution");
+        assert_eq!((resolution.x, resolution.y, resolution.unit), (x, y, unit));
+    }
+
+    #[test]
+    fn png_resolution_comes_from_the_phys_chunk_in_meters() {
+        let directory = directory("png-resolution");
+        let meters = directory.join("meters.png");
+        png(&meters, Some(png::PixelDimensions { xppu: 11811, yppu: 5906, unit: png::Unit::Meter }));
+        assert_resolution(png_resolution(&meters), 11811., 5906., ResolutionUnit::Meter);
+        assert_resolution(image_resolution(&meters), 11811., 5906., ResolutionUnit::Meter);
+
+        // Without unit the chunk only gives the aspect ratio of the pixels
+        let unitless = directory.join("unitless.png");
+        png(&unitless, Some(png::PixelDimensions { xppu: 2, yppu: 1, unit: png::Unit::Unspecified }));
+        assert!(png_resolution(&unitless).is_none());
+
+        let missing = directory.join("missing.png");
+        png(&missing, None);
+        assert!(png_resolution(&missing).is_none());
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn tiff_resolution_comes_from_the_tags_in_their_unit() {
+        use tiff::tags::ResolutionUnit as TiffUnit;
+
+        let directory = directory("tiff-resolution");
+        let centimeters = directory.join("centimeters.tif");
+        tiff(&centimeters, Some((TiffUnit::Centimeter, Rational { n: 401, d: 2 }, Rational { n: 100, d: 1 })));
+        assert_resolution(tiff_resolution(&centimeters), 200.5, 100., ResolutionUnit::Centimeter);
+        assert_resolution(image_resolution(&centimeters), 200.5, 100., ResolutionUnit::Centimeter);
+
+        let inches = directory.join("inches.tiff");
+        tiff(&inches, Some((TiffUnit::Inch, Rational { n: 300, d: 1 }, Rational { n: 300, d: 1 })));
+        assert_resolution(tiff_resolution(&inches), 300., 300., ResolutionUnit::Inch);
+
+        // The encoder writes a resolution of 1 without unit by default
+        let unitless = directory.join("unitless.tif");
+        tiff(&unitless, None);
+        assert!(tiff_resolution(&unitless).is_none());
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn other_formats_have_no_resolution() {
+        let directory = directory("other-resolution");
+        let bmp = directory.join("image.bmp");
+        RgbaImage::new(2, 2).save(&bmp).unwrap();
+        assert!(image_resolution(&bmp).is_none());
+        assert!(image_resolution(&directory.join("image.unknown")).is_none());
+        fs::remove_dir_all(directory).unwrap();
     }
 }
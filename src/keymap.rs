@@ -0,0 +1,99 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use eframe::egui::Key;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Action {
+    DragMode,
+    SelectionMode,
+    LineMode,
+    CircleMode,
+    Cancel,
+    Delete,
+}
+
+impl Action {
+    pub const ALL: [Action; 6] = [
+        Action::DragMode,
+        Action::SelectionMode,
+        Action::LineMode,
+        Action::CircleMode,
+        Action::Cancel,
+        Action::Delete,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::DragMode => "Drag mode",
+            Action::SelectionMode => "Selection mode",
+            Action::LineMode => "Line mode",
+            Action::CircleMode => "Circle mode",
+            Action::Cancel => "Cancel current shape",
+            Action::Delete => "Delete selected shape",
+        }
+    }
+}
+
+/// Key bindings of actions, saved in the user configuration directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Keymap {
+    bindings: BTreeMap<Action, Key>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            bindings: BTreeMap::from([
+                (Action::DragMode, Key::H),
+                (Action::SelectionMode, Key::S),
+                (Action::LineMode, Key::L),
+                (Action::CircleMode, Key::C),
+                (Action::Cancel, Key::Escape),
+                (Action::Delete, Key::Delete),
+            ]),
+        }
+    }
+}
+
+impl Keymap {
+    fn path() -> io::Result<PathBuf> {
+        dirs::config_dir()
+            .map(|dir| dir.join("bluekompass").join("keymap.json"))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no configuration directory"))
+    }
+
+    /// Load the keymap from the configuration file or fall back on the default one
+    pub fn load() -> Self {
+        Self::path()
+            .and_then(fs::read_to_string)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = Self::path()?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn key(&self, action: Action) -> Option<Key> {
+        self.bindings.get(&action).copied()
+    }
+
+    /// Bind a key to an action, removing it from any other action
+    pub fn bind(&mut self, action: Action, key: Key) {
+        self.bindings.retain(|_, bound| *bound != key);
+        self.bindings.insert(action, key);
+    }
+
+    pub fn unbind(&mut self, action: Action) {
+        self.bindings.remove(&action);
+    }
+}
@@ -2,13 +2,46 @@
 
 mod app;
 mod image_loader;
-mod shapes;
+mod render;
 mod builders;
-mod maths;
+mod keymap;
+mod cli;
+mod raster;
 use self::app::BlueKompassApp;
-use eframe;
+use self::keymap::Keymap;
+
+/// Write the output of the commands to the console they are run from, release builds on Windows have none
+#[cfg(windows)]
+fn attach_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+    // SAFETY: takes no pointer, fails without effect when the parent has no console or one is already attached
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_console() {}
 
 fn main() -> Result<(), eframe::Error> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("measure") => {
+            attach_console();
+            std::process::exit(cli::measure_command(&args[1..]))
+        }
+        Some("render") => {
+            attach_console();
+            std::process::exit(cli::render_command(&args[1..]))
+        }
+        _ => {}
+    }
+    let startup_options = cli::startup_options(&args).unwrap_or_else(|status| std::process::exit(status));
+
     // env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
     let icon = include_bytes!("../assets/bluekompass.png");
     let options = eframe::NativeOptions {
@@ -22,7 +55,7 @@ fn main() -> Result<(), eframe::Error> {
         options,
         Box::new(|cc| {
             egui_extras::install_image_loaders(&cc.egui_ctx);
-            Box::<BlueKompassApp>::default()
+            Box::new(BlueKompassApp::new(startup_options, Keymap::load()))
         }),
     )
 }
@@ -0,0 +1,261 @@
+//! Off-screen rendering of an image with its shapes burned in, following what `Draw::draw` shows on the plot
+
+use std::f32::consts::FRAC_1_SQRT_2;
+use std::io;
+use std::path::Path;
+
+use ab_glyph::{point, Font, FontRef, PxScale, ScaleFont};
+use eframe::egui::FontDefinitions;
+use image::{DynamicImage, Rgba, RgbaImage};
+use tiny_skia::{
+    FillRule, FilterQuality, IntSize, LineCap, Paint, PathBuilder, Pixmap, PixmapPaint, Stroke, StrokeDash, Transform,
+};
+
+use bluekompass_core::calibration::Calibration;
+use bluekompass_core::color::Color;
+use bluekompass_core::coordinates::plot_to_pixel;
+use bluekompass_core::layers::Layer;
+use bluekompass_core::measure::label;
+use bluekompass_core::point::Point;
+use bluekompass_core::shapes::{Geometry, Shape};
+use bluekompass_core::style::{Dash, Style};
+
+use crate::render::{label_color, LABEL_SIZE};
+
+/// Font of the labels, the one egui uses for plot texts
+const LABEL_FONT: &str = "Ubuntu-Light";
+
+pub struct RasterOptions {
+    /// Size of the output relative to the native resolution of the image
+    pub scale: f32,
+    pub labels: bool,
+}
+
+impl Default for RasterOptions {
+    fn default() -> Self {
+        Self { scale: 1.0, labels: true }
+    }
+}
+
+fn paint(color: Color) -> Paint<'static> {
+    let [r, g, b, a] = color.to_array();
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(r, g, b, a);
+    paint.anti_alias = true;
+    paint
+}
+
+/// Stroke of a shape, dashes and dots follow the `LineStyle` of the plot
+fn stroke(style: &Style, layer: &Layer) -> Stroke {
+    let width = style.width(layer);
+    let mut stroke = Stroke { width, ..Stroke::default() };
+    match (style.dash, style.dash_pattern(layer)) {
+        (Dash::Dashed, Some([dash, _])) => {
+            // egui_plot separates dashes by the golden ratio of their length
+            stroke.dash = StrokeDash::new(vec![dash, dash * 0.618_034], 0.);
+        }
+        (Dash::Dotted, Some([dot, gap])) => {
+            // Dots are round with the stroke width as radius
+            stroke.width = 2. * width;
+            stroke.line_cap = LineCap::Round;
+            stroke.dash = StrokeDash::new(vec![0.01, dot + gap], 0.);
+        }
+        _ => {}
+    }
+    stroke
+}
+
+struct Canvas {
+    pixmap: Pixmap,
+    scale: f64,
+}
+
+impl Canvas {
+    fn to_pixel(&self, point: Point) -> (f32, f32) {
+        let pixel = plot_to_pixel(point);
+        ((pixel.x * self.scale) as f32, (pixel.y * self.scale) as f32)
+    }
+
+    fn segment(&mut self, [a, b]: [(f32, f32); 2], color: Color, stroke: &Stroke) {
+        let mut path = PathBuilder::new();
+        path.move_to(a.0, a.1);
+        path.line_to(b.0, b.1);
+        if let Some(path) = path.finish() {
+            self.pixmap.stroke_path(&path, &paint(color), stroke, Transform::identity(), None);
+        }
+    }
+
+    fn disc(&mut self, (x, y): (f32, f32), radius: f32, color: Color) {
+        if let Some(path) = PathBuilder::from_circle(x, y, radius) {
+            self.pixmap.fill_path(&path, &paint(color), FillRule::Winding, Transform::identity(), None);
+        }
+    }
+
+    /// Grip points as filled circles with a white outline, as `draw_grips`
+    fn grips(&mut self, points: &[Point], color: Color, marker_size: f32) {
+        for &point in points {
+            let position = self.to_pixel(point);
+            self.disc(position, marker_size, Color::WHITE);
+            self.disc(position, marker_size - 1., color);
+        }
+    }
+
+    fn shape(&mut self, shape: &dyn Shape, layer: &Layer) {
+        let style = &shape.properties().style;
+        let color = style.color(layer);
+        let stroke = stroke(style, layer);
+        let Some(geometry) = shape.geometry() else {
+            return;
+        };
+
+        match geometry {
+            Geometry::Line(points) => {
+                self.segment(points.map(|point| self.to_pixel(point)), color, &stroke);
+            }
+            Geometry::Circle { center, radius } => {
+                let (x, y) = self.to_pixel(center);
+                if let Some(path) = PathBuilder::from_circle(x, y, (radius * self.scale) as f32) {
+                    self.pixmap.stroke_path(&path, &paint(color), &stroke, Transform::identity(), None);
+                }
+
+                // Center of circle, a cross marker of the plot
+                let radius = style.marker_size - 1.;
+                let offset = radius * FRAC_1_SQRT_2;
+                let cross = Stroke { width: radius / 5., ..Stroke::default() };
+                self.segment([(x - offset, y - offset), (x + offset, y + offset)], color, &cross);
+                self.segment([(x + offset, y - offset), (x - offset, y + offset)], color, &cross);
+            }
+        }
+
+        if style.show_grips {
+            self.grips(shape.as_slice(), color, style.marker_size);
+        }
+    }
+
+    fn into_image(self) -> RgbaImage {
+        let (width, height) = (self.pixmap.width(), self.pixmap.height());
+        let pixels = self.pixmap.pixels()
+            .iter()
+            .flat_map(|pixel| {
+                let pixel = pixel.demultiply();
+                [pixel.red(), pixel.green(), pixel.blue(), pixel.alpha()]
+            })
+            .collect();
+        RgbaImage::from_raw(width, height, pixels).expect("pixmap and image have the same size")
+    }
+}
+
+/// Blend a text whose bottom left corner is at `position`, as a plot text anchored at `LEFT_BOTTOM`
+fn draw_text(image: &mut RgbaImage, font: &FontRef, text: &str, (x, y): (f32, f32), color: Color) {
+    let font = font.as_scaled(PxScale::from(LABEL_SIZE));
+    let baseline = y + font.descent();
+    let [r, g, b, a] = color.to_array();
+
+    let mut caret = x;
+    let mut previous = None;
+    for character in text.chars() {
+        let id = font.glyph_id(character);
+        if let Some(previous) = previous {
+            caret += font.kern(previous, id);
+        }
+        previous = Some(id);
+        let glyph = id.with_scale_and_position(LABEL_SIZE, point(caret, baseline));
+        caret += font.h_advance(id);
+
+        let Some(outline) = font.outline_glyph(glyph) else {
+            continue;
+        };
+        let bounds = outline.px_bounds();
+        outline.draw(|gx, gy, coverage| {
+            let (px, py) = (bounds.min.x as i64 + gx as i64, bounds.min.y as i64 + gy as i64);
+            if px < 0 || py < 0 || px >= image.width() as i64 || py >= image.height() as i64 {
+                return;
+            }
+            let alpha = coverage.clamp(0., 1.) * a as f32 / 255.;
+            let Rgba(pixel) = image.get_pixel_mut(px as u32, py as u32);
+            for (channel, source) in pixel.iter_mut().zip([r, g, b, 255]) {
+                *channel = (source as f32 * alpha + *channel as f32 * (1. - alpha)).round() as u8;
+            }
+        });
+    }
+}
+
+/// Render the image with every visible shape and, optionally, their labels
+pub fn render(
+    image: &RgbaImage,
+    shapes: &[Box<dyn Shape>],
+    layers: &[Layer],
+    calibration: &Calibration,
+    options: &RasterOptions,
+) -> io::Result<RgbaImage> {
+    let invalid_size = || io::Error::new(io::ErrorKind::InvalidInput, "invalid size of the rendered image");
+    let scale = options.scale as f64;
+    let size = [image.width(), image.height()].map(|length| (length as f64 * scale).round().max(1.) as u32);
+
+    // tiny-skia works on premultiplied colors
+    let premultiplied = image.pixels()
+        .flat_map(|&Rgba([r, g, b, a])| {
+            let premultiply = |channel: u8| (channel as u16 * a as u16 / 255) as u8;
+            [premultiply(r), premultiply(g), premultiply(b), a]
+        })
+        .collect();
+    let source = IntSize::from_wh(image.width(), image.height())
+        .and_then(|size| Pixmap::from_vec(premultiplied, size))
+        .ok_or_else(invalid_size)?;
+
+    let mut canvas = Canvas {
+        pixmap: Pixmap::new(size[0], size[1]).ok_or_else(invalid_size)?,
+        scale,
+    };
+    canvas.pixmap.draw_pixmap(
+        0,
+        0,
+        source.as_ref(),
+        &PixmapPaint { quality: FilterQuality::Bilinear, ..PixmapPaint::default() },
+        Transform::from_scale(options.scale, options.scale),
+        None,
+    );
+
+    let visible: Vec<&dyn Shape> = shapes.iter()
+        .map(|shape| shape.as_ref())
+        .filter(|shape| {
+            let properties = shape.properties();
+            properties.visible && layers[properties.layer].visible && shape.is_valid()
+        })
+        .collect();
+    for &shape in &visible {
+        canvas.shape(shape, &layers[shape.properties().layer]);
+    }
+
+    let labels: Vec<_> = visible.iter()
+        .filter(|_| options.labels)
+        .map(|&shape| {
+            let layer = &layers[shape.properties().layer];
+            (label(shape, calibration), canvas.to_pixel(shape.center()), label_color(shape, layer, calibration))
+        })
+        .collect();
+    let mut output = canvas.into_image();
+    if !labels.is_empty() {
+        let fonts = FontDefinitions::default();
+        let font = fonts.font_data.get(LABEL_FONT)
+            .and_then(|data| FontRef::try_from_slice(&data.font).ok())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no font for labels"))?;
+        for (text, position, color) in labels {
+            draw_text(&mut output, &font, &text, position, color);
+        }
+    }
+    Ok(output)
+}
+
+/// Save a rendered image, in a format depending on the extension of the file
+pub fn save(image: RgbaImage, file: &Path) -> io::Result<()> {
+    let is_jpeg = file.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("jpg") || extension.eq_ignore_ascii_case("jpeg"));
+    let result = if is_jpeg {
+        // JPEG has no alpha channel
+        DynamicImage::ImageRgba8(image).to_rgb8().save(file)
+    } else {
+        image.save(file)
+    };
+    result.map_err(io::Error::other)
+}
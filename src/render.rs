@@ -0,0 +1,168 @@
+use std::f64::consts::TAU;
+use egui_plot::{LineStyle, PlotPoint, PlotPoints, PlotUi, MarkerShape};
+use eframe::{egui, epaint};
+use egui::{remap, Response, RichText, Ui};
+
+use bluekompass_core::calibration::Calibration;
+use bluekompass_core::color::Color;
+use bluekompass_core::layers::Layer;
+use bluekompass_core::measure::shape_passed;
+use bluekompass_core::point::Point;
+use bluekompass_core::shapes::{Geometry, Shape};
+use bluekompass_core::style::{Dash, Style};
+
+pub const BLUE: egui::Color32 = epaint::Color32::from_rgb(46, 101, 255);
+pub const RED: egui::Color32 = epaint::Color32::from_rgb(220, 50, 47);
+pub const GREEN: egui::Color32 = epaint::Color32::from_rgb(40, 170, 60);
+/// Font size of the labels of shapes, also used when rasterizing them
+pub const LABEL_SIZE: f32 = 14.0;
+
+pub fn to_plot_point(point: Point) -> PlotPoint {
+    PlotPoint::new(point.x, point.y)
+}
+
+pub fn to_point(point: PlotPoint) -> Point {
+    Point::new(point.x, point.y)
+}
+
+pub fn to_color32(color: Color) -> egui::Color32 {
+    let [r, g, b, a] = color.to_array();
+    egui::Color32::from_rgba_unmultiplied(r, g, b, a)
+}
+
+pub fn color_edit_button(ui: &mut Ui, color: &mut Color) -> Response {
+    let mut color32 = to_color32(*color);
+    let response = ui.color_edit_button_srgba(&mut color32);
+    if response.changed() {
+        *color = Color(color32.to_srgba_unmultiplied());
+    }
+    response
+}
+
+fn stroke(style: &Style, layer: &Layer) -> epaint::Stroke {
+    epaint::Stroke::new(style.width(layer), to_color32(style.color(layer)))
+}
+
+fn line_style(style: &Style, layer: &Layer) -> LineStyle {
+    match (style.dash, style.dash_pattern(layer)) {
+        (Dash::Dashed, Some([dash, _])) => LineStyle::Dashed { length: dash },
+        (Dash::Dotted, Some([dot, gap])) => LineStyle::Dotted { spacing: dot + gap },
+        _ => LineStyle::Solid,
+    }
+}
+
+/// Draw grip points as filled circles with a white outline
+pub fn draw_grips(plot_ui: &mut PlotUi, points: &[Point], color: egui::Color32, marker_size: f32) {
+    let points: Vec<PlotPoint> = points.iter().map(|&point| to_plot_point(point)).collect();
+
+    // Stroke color for points
+    plot_ui.points(
+        egui_plot::Points::new(PlotPoints::Owned(points.clone()))
+            .radius(marker_size)
+            .filled(true)
+            .shape(MarkerShape::Circle)
+            .color(epaint::Color32::WHITE)
+    );
+
+    // Fill color for points
+    plot_ui.points(
+        egui_plot::Points::new(PlotPoints::Owned(points))
+            .radius(marker_size - 1.)
+            .filled(true)
+            .shape(MarkerShape::Circle)
+            .color(color)
+    );
+}
+
+/// Color of the label of a shape, green or red when its measurements have tolerances
+pub fn label_color(shape: &dyn Shape, layer: &Layer, calibration: &Calibration) -> Color {
+    match shape_passed(shape, calibration) {
+        Some(true) => Color(GREEN.to_srgba_unmultiplied()),
+        Some(false) => Color(RED.to_srgba_unmultiplied()),
+        None => shape.properties().style.color(layer),
+    }
+}
+
+/// Draw a label with its bottom left corner on the center of the shape
+pub fn draw_label(plot_ui: &mut PlotUi, shape: &dyn Shape, color: Color, text: String) {
+    plot_ui.text(
+        egui_plot::Text::new(to_plot_point(shape.center()), RichText::new(text).size(LABEL_SIZE))
+            .color(to_color32(color))
+            .anchor(egui::Align2::LEFT_BOTTOM)
+    );
+}
+
+pub trait Draw {
+    fn draw(&self, plot_ui: &mut PlotUi, layer: &Layer);
+}
+
+impl Draw for dyn Shape {
+    fn draw(&self, plot_ui: &mut PlotUi, layer: &Layer) {
+        let style = &self.properties().style;
+        let stroke = stroke(style, layer);
+
+        match self.geometry() {
+            None => {
+                // Warn about degenerate points instead of drawing a meaningless shape
+                let points: Vec<PlotPoint> = self.as_slice().iter().map(|&point| to_plot_point(point)).collect();
+                plot_ui.line(
+                    egui_plot::Line::new(PlotPoints::Owned(points))
+                        .stroke(epaint::Stroke::new(stroke.width, RED))
+                        .style(LineStyle::dashed_dense())
+                );
+                plot_ui.text(
+                    egui_plot::Text::new(
+                        to_plot_point(self.center()),
+                        format!("⚠ Degenerate {}: points are collinear or coincident", self.kind().name().to_lowercase()),
+                    )
+                    .color(RED)
+                    .anchor(egui::Align2::LEFT_BOTTOM)
+                );
+                draw_grips(plot_ui, self.as_slice(), RED, style.marker_size);
+                return;
+            }
+            Some(Geometry::Line(points)) => {
+                // Line
+                plot_ui.line(
+                    egui_plot::Line::new(PlotPoints::Owned(points.map(to_plot_point).to_vec()))
+                        .stroke(stroke)
+                        .style(line_style(style, layer))
+                );
+            }
+            Some(Geometry::Circle { center, radius }) => {
+                let n = 512;
+
+                // Circle
+                plot_ui.line(
+                    egui_plot::Line::new(
+                        (0..=n).map(
+                            |i| {
+                                let t = remap(i as f64, 0.0..=(n as f64), 0.0..=TAU);
+                                [
+                                    radius * t.cos() + center.x,
+                                    radius * t.sin() + center.y,
+                                ]
+                            }
+                        ).collect::<PlotPoints>()
+                    )
+                    .stroke(stroke)
+                    .style(line_style(style, layer))
+                );
+
+                // Center of circle
+                plot_ui.points(
+                    egui_plot::Points::new(PlotPoints::Owned(vec![to_plot_point(center)]))
+                        .radius(style.marker_size - 1.)
+                        .filled(true)
+                        .shape(MarkerShape::Cross)
+                        .color(stroke.color)
+                );
+            }
+        }
+
+        if style.show_grips || self.is_selected() {
+            let color = if self.is_selected() { BLUE } else { stroke.color };
+            draw_grips(plot_ui, self.as_slice(), color, style.marker_size);
+        }
+    }
+}